@@ -1,25 +1,26 @@
+use super::dry_run;
 use crate::{git, Project, Storage};
 use anyhow::Result;
 use std::path::PathBuf;
 
-pub fn add(path: PathBuf, name: Option<String>, tags: Option<Vec<String>>) -> Result<()> {
+pub fn add(
+    path: PathBuf,
+    name: Option<String>,
+    tags: Option<Vec<String>>,
+    dry_run: bool,
+) -> Result<()> {
     let path = std::fs::canonicalize(&path)?;
 
-    let name = name.unwrap_or_else(|| {
-        path.file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown")
-            .to_string()
-    });
+    let name = name.unwrap_or_else(|| Project::default_name(&path));
 
     let is_bare = git::is_bare_repo(&path);
-    let mut project = Project::new(name.clone(), path.clone(), is_bare);
+    let mut project = Project::new(name.clone(), path.clone(), is_bare).with_readme_description();
 
     if let Some(tags) = tags {
         project = project.with_tags(tags);
     }
 
-    let mut storage = Storage::load()?;
+    let mut storage = Storage::load()?.dry_run(dry_run);
     storage.add(project)?;
 
     println!("Project '{}' added", name);
@@ -28,5 +29,5 @@ pub fn add(path: PathBuf, name: Option<String>, tags: Option<Vec<String>>) -> Re
     }
     println!("  Path: {}", path.display());
 
-    Ok(())
+    dry_run::report(&storage)
 }
@@ -0,0 +1,59 @@
+use super::dry_run;
+use crate::{
+    cli::Cli,
+    storage::{Storage, StorageError},
+};
+use anyhow::{bail, Result};
+
+pub fn alias(project: String, aliases: Vec<String>, remove: bool, dry_run: bool) -> Result<()> {
+    let mut storage = Storage::load()?.dry_run(dry_run);
+
+    if aliases.is_empty() {
+        let found = storage
+            .find_by_name(&project)
+            .ok_or_else(|| StorageError::ProjectNotFound(project.clone()))?;
+        for alias in &found.aliases {
+            println!("{alias}");
+        }
+        return Ok(());
+    }
+
+    for alias in &aliases {
+        if remove {
+            storage.remove_alias(&project, alias)?;
+        } else {
+            validate(alias)?;
+            storage.add_alias(&project, alias)?;
+        }
+    }
+
+    let action = if remove { "removed from" } else { "added to" };
+    println!("Aliases {} '{}'", action, project);
+
+    dry_run::report(&storage)
+}
+
+/// Aliases are typed as `bvo <alias>`, so they cannot look like a flag or be
+/// a subcommand, a subcommand alias or `help`.
+fn validate(alias: &str) -> Result<()> {
+    if alias.is_empty() || alias.starts_with('-') || alias.contains(char::is_whitespace) {
+        bail!("invalid alias '{alias}'");
+    }
+    if Cli::subcommand_names().iter().any(|c| c == alias) {
+        bail!("'{alias}' is a bvo command and cannot be an alias");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_rejects_subcommands() {
+        assert!(validate("fe").is_ok());
+        for taken in ["list", "rm", "help", "-x", "two words", ""] {
+            assert!(validate(taken).is_err(), "{taken}");
+        }
+    }
+}
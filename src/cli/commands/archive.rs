@@ -0,0 +1,23 @@
+use super::dry_run;
+use crate::storage::Storage;
+use anyhow::Result;
+
+/// Archives (`archived = true`) or restores a project. Archiving also unpins
+/// it, since pinning a hidden project has no effect.
+pub fn archive(name: String, archived: bool, dry_run: bool) -> Result<()> {
+    let mut storage = Storage::load()?.dry_run(dry_run);
+    storage.update(&name, |project| {
+        project.archived = archived;
+        if archived {
+            project.pinned = false;
+        }
+    })?;
+
+    if archived {
+        println!("Project '{name}' archived");
+    } else {
+        println!("Project '{name}' unarchived");
+    }
+
+    dry_run::report(&storage)
+}
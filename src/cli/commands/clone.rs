@@ -0,0 +1,86 @@
+use super::dry_run;
+use crate::{
+    config::Config, git, remote::RemoteUrl, storage::StorageError, utils::fs::expand_home, Project,
+    Storage,
+};
+use anyhow::{bail, Result};
+use std::path::PathBuf;
+
+pub fn clone(
+    url: String,
+    name: Option<String>,
+    tags: Option<Vec<String>>,
+    bare: bool,
+    root: Option<PathBuf>,
+    dry_run: bool,
+) -> Result<()> {
+    let config = Config::load()?;
+    let remote = RemoteUrl::parse(&url)?;
+
+    let root = root
+        .map(|r| expand_home(&r))
+        .unwrap_or_else(|| config.clone_root());
+    let dest = remote.destination(config.clone_layout(), &root);
+    let name = name.unwrap_or_else(|| remote.repo.clone());
+
+    // Checked before cloning so a clash never leaves an unregistered clone.
+    let mut storage = Storage::load()?.dry_run(dry_run);
+    storage.check_available(&name, None)?;
+    if dest.exists() {
+        bail!("destination '{}' already exists", dest.display());
+    }
+    if storage.find_by_path(&dest).is_some() {
+        return Err(StorageError::DuplicateProjectPath(dest.to_string_lossy().to_string()).into());
+    }
+
+    if dry_run {
+        // Nothing is cloned, so the project is shown as it would be added.
+        println!("Would clone into '{}'", dest.display());
+        let project = Project::new(name, dest, bare).with_tags(tags.unwrap_or_default());
+        storage.add(project)?;
+        return dry_run::report(&storage);
+    }
+
+    println!("Cloning into '{}'...", dest.display());
+    let mut worktree_path = None;
+    let added = (|| -> Result<PathBuf> {
+        if bare {
+            let branch = git::clone_bare(&url, &dest)?;
+            let path = config.worktree_path(&dest, &name, &branch);
+            if !path.exists() {
+                worktree_path = Some(path.clone());
+            }
+            let worktree = git::add_worktree(&dest, &branch, &path, None)?;
+            println!("  Worktree: {}", worktree.path.display());
+        } else {
+            git::clone_repo(&url, &dest)?;
+        }
+
+        let dest = std::fs::canonicalize(&dest)?;
+        let mut project = Project::new(name.clone(), dest.clone(), git::is_bare_repo(&dest))
+            .with_readme_description();
+
+        if let Some(tags) = tags {
+            project = project.with_tags(tags);
+        }
+
+        storage.add(project)?;
+        Ok(dest)
+    })();
+
+    // A clone that could not be registered, or only half made, is removed.
+    let dest = match added {
+        Ok(dest) => dest,
+        Err(e) => {
+            for path in worktree_path.iter().chain([&dest]) {
+                let _ = std::fs::remove_dir_all(path);
+            }
+            return Err(e);
+        }
+    };
+
+    println!("Project '{}' added", name);
+    println!("  Path: {}", dest.display());
+
+    Ok(())
+}
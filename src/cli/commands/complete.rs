@@ -0,0 +1,15 @@
+use crate::storage::Storage;
+use anyhow::Result;
+
+/// Prints project names and aliases, one per line, for shell completion.
+pub fn complete() -> Result<()> {
+    let storage = Storage::load()?;
+    for project in storage.list().into_iter().filter(|p| !p.archived) {
+        println!("{}", project.name);
+        for alias in &project.aliases {
+            println!("{alias}");
+        }
+    }
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use crate::{cli::ConfigAction, config::Config, storage::Storage};
+use crate::{cli::ConfigAction, config::Config, profile, storage::Storage};
 use anyhow::Result;
 
 pub fn config(action: ConfigAction) -> Result<()> {
@@ -10,6 +10,7 @@ pub fn config(action: ConfigAction) -> Result<()> {
             let config_path = Config::path();
 
             println!("Config Path: {}", config_path.display());
+            println!("Profile: {}", profile::active());
             println!("Storage Path: {}", storage_path.display());
 
             println!();
@@ -0,0 +1,43 @@
+use super::dry_run;
+use crate::storage::{Storage, StorageError};
+use anyhow::{bail, Result};
+
+pub fn describe(
+    name: String,
+    description: Option<String>,
+    readme: bool,
+    clear: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let mut storage = Storage::load()?.dry_run(dry_run);
+    let project = storage
+        .find_by_name(&name)
+        .ok_or_else(|| StorageError::ProjectNotFound(name.clone()))?;
+
+    let description = if clear {
+        None
+    } else if readme {
+        let mut seeded = project.clone();
+        seeded.description = None;
+        match seeded.with_readme_description().description {
+            Some(description) => Some(description),
+            None => bail!("no README paragraph found in {}", project.path.display()),
+        }
+    } else if let Some(description) = description {
+        Some(description.trim().to_string()).filter(|d| !d.is_empty())
+    } else {
+        match &project.description {
+            Some(description) => println!("{description}"),
+            None => println!("(no description)"),
+        }
+        return Ok(());
+    };
+
+    storage.update(&name, |project| project.description = description.clone())?;
+    match description {
+        Some(description) => println!("Description of '{name}' set to: {description}"),
+        None => println!("Description of '{name}' cleared"),
+    }
+
+    dry_run::report(&storage)
+}
@@ -0,0 +1,238 @@
+use super::dry_run;
+use crate::{config::Config, git, remote::RemoteUrl, storage::Storage, Project};
+use anyhow::Result;
+use inquire::{Confirm, Select};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Depth searched below each root when looking for a moved repository. Enough
+/// for the default `{root}/{host}/{owner}/{repo}` clone layout.
+const SEARCH_DEPTH: usize = 4;
+
+enum Problem {
+    InvalidConfig(String),
+    MissingPath(Project),
+    BareMismatch(Project),
+    StaleWorktrees(Project, Vec<String>),
+    /// The first project points to the same directory as the second one.
+    DuplicatePath(Project, Box<Project>),
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidConfig(e) => write!(f, "config file cannot be parsed: {e}"),
+            Self::MissingPath(p) => write!(f, "'{}': {} does not exist", p.name, p.path.display()),
+            Self::BareMismatch(p) => {
+                let (registered, actual) = if p.is_bare_repo {
+                    ("bare", "not bare")
+                } else {
+                    ("not bare", "bare")
+                };
+                write!(
+                    f,
+                    "'{}': registered as {registered} but is {actual}",
+                    p.name
+                )
+            }
+            Self::StaleWorktrees(p, names) => write!(
+                f,
+                "'{}': worktrees with deleted directories: {}",
+                p.name,
+                names.join(", ")
+            ),
+            Self::DuplicatePath(p, other) => write!(
+                f,
+                "'{}': same directory as '{}' ({})",
+                p.name,
+                other.name,
+                p.path.display()
+            ),
+        }
+    }
+}
+
+pub fn doctor(check: bool, dry_run: bool) -> Result<()> {
+    let config = Config::load();
+    let storage = Storage::load()?.dry_run(dry_run);
+
+    let mut problems = Vec::new();
+    if let Err(e) = &config {
+        problems.push(Problem::InvalidConfig(e.to_string()));
+    }
+    problems.extend(diagnose(&storage.list()));
+
+    if problems.is_empty() {
+        println!("No problems found");
+        return Ok(());
+    }
+
+    for problem in &problems {
+        println!("{problem}");
+    }
+
+    if check {
+        std::process::exit(1);
+    }
+
+    let config = config.unwrap_or_default();
+    let mut storage = storage;
+    println!();
+    for problem in problems {
+        fix(problem, &config, &mut storage)?;
+    }
+
+    dry_run::report(&storage)
+}
+
+fn diagnose(projects: &[&Project]) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    // Canonical path -> first project seen there, highest frecency first.
+    let mut seen: HashMap<PathBuf, &Project> = HashMap::new();
+
+    for &project in projects {
+        if !project.exists() {
+            problems.push(Problem::MissingPath(project.clone()));
+            continue;
+        }
+
+        if git::is_bare_repo(&project.path) != project.is_bare_repo {
+            problems.push(Problem::BareMismatch(project.clone()));
+        }
+
+        if project.is_bare_repo {
+            match git::stale_worktrees(&project.path) {
+                Ok(stale) if !stale.is_empty() => {
+                    problems.push(Problem::StaleWorktrees(project.clone(), stale));
+                }
+                _ => {}
+            }
+        }
+
+        let canonical = fs::canonicalize(&project.path).unwrap_or(project.path.clone());
+        match seen.get(&canonical) {
+            Some(other) => {
+                problems.push(Problem::DuplicatePath(
+                    project.clone(),
+                    Box::new((*other).clone()),
+                ));
+            }
+            None => {
+                seen.insert(canonical, project);
+            }
+        }
+    }
+
+    problems
+}
+
+fn fix(problem: Problem, config: &Config, storage: &mut Storage) -> Result<()> {
+    match &problem {
+        Problem::InvalidConfig(_) => {
+            let backup = Config::path().with_extension("json.bak");
+            let message = format!("Move config to {} and reset it?", backup.display());
+            if storage.is_dry_run() {
+                println!("Would move config to {}", backup.display());
+            } else if Confirm::new(&message).with_default(false).prompt()? {
+                fs::rename(Config::path(), &backup)?;
+                println!("Config reset");
+            }
+        }
+        Problem::MissingPath(project) => {
+            const REMOVE: &str = "Remove from registry";
+            const SKIP: &str = "Skip";
+
+            let candidates = find_candidates(project, config, storage);
+            let mut options: Vec<String> = candidates
+                .iter()
+                .map(|path| format!("Relocate to {}", path.display()))
+                .collect();
+            options.push(REMOVE.to_string());
+            options.push(SKIP.to_string());
+
+            let message = format!("{problem}. Fix:");
+            let choice = Select::new(&message, options).prompt()?;
+            if choice == REMOVE {
+                storage.remove(&project.name)?;
+                println!("Project '{}' removed", project.name);
+            } else if let Some(path) = candidates
+                .into_iter()
+                .find(|path| choice == format!("Relocate to {}", path.display()))
+            {
+                let is_bare = git::is_bare_repo(&path);
+                storage.relocate(&project.name, path.clone(), is_bare)?;
+                println!(
+                    "Project '{}' now points to {}",
+                    project.name,
+                    path.display()
+                );
+            }
+        }
+        Problem::BareMismatch(project) => {
+            let message = format!("{problem}. Re-detect?");
+            if Confirm::new(&message).with_default(true).prompt()? {
+                let is_bare = git::is_bare_repo(&project.path);
+                storage.update(&project.name, |p| p.is_bare_repo = is_bare)?;
+                println!("Project '{}' updated", project.name);
+            }
+        }
+        Problem::StaleWorktrees(project, _) => {
+            let message = format!("{problem}. Prune them?");
+            if storage.is_dry_run() {
+                println!("Would prune worktrees of '{}'", project.name);
+            } else if Confirm::new(&message).with_default(true).prompt()? {
+                for name in git::prune_worktrees(&project.path)? {
+                    println!("Worktree '{}' pruned", name);
+                }
+            }
+        }
+        Problem::DuplicatePath(project, _) => {
+            let message = format!("{problem}. Remove '{}'?", project.name);
+            if Confirm::new(&message).with_default(true).prompt()? {
+                storage.remove(&project.name)?;
+                println!("Project '{}' removed", project.name);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Unregistered repositories that could be where `project` moved to: same
+/// directory name, or an `origin` remote with the same repository name.
+/// Searched under the clone root and the closest surviving parent of the old
+/// path.
+fn find_candidates(project: &Project, config: &Config, storage: &Storage) -> Vec<PathBuf> {
+    let old_name = Project::default_name(&project.path);
+    let matches_name = |name: &str| name == project.name || name == old_name;
+
+    let mut roots = vec![config.clone_root()];
+    if let Some(parent) = project.path.ancestors().skip(1).find(|p| p.is_dir()) {
+        if parent != Path::new("/") && !roots.iter().any(|r| r == parent) {
+            roots.push(parent.to_path_buf());
+        }
+    }
+
+    let ignore = ["node_modules", "target", "vendor", ".venv"].map(String::from);
+    let mut candidates: Vec<PathBuf> = roots
+        .iter()
+        .filter(|root| root.is_dir())
+        .flat_map(|root| {
+            git::discover_repositories(root, Some(SEARCH_DEPTH), &ignore).unwrap_or_default()
+        })
+        .map(|repo| repo.path)
+        .filter(|path| storage.find_by_path(path).is_none())
+        .filter(|path| {
+            matches_name(&Project::default_name(path))
+                || git::origin_url(path)
+                    .and_then(|url| RemoteUrl::parse(&url).ok())
+                    .is_some_and(|remote| matches_name(&remote.repo))
+        })
+        .collect();
+
+    candidates.sort();
+    candidates.dedup();
+    candidates
+}
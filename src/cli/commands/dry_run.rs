@@ -0,0 +1,19 @@
+use crate::storage::Storage;
+use anyhow::Result;
+
+/// Prints what a dry-run `storage` would have changed. Does nothing when
+/// dry-run is off.
+pub(super) fn report(storage: &Storage) -> Result<()> {
+    if !storage.is_dry_run() {
+        return Ok(());
+    }
+
+    println!();
+    match storage.dry_run_diff()? {
+        Some(diff) => print!("{}", diff),
+        None => println!("No changes"),
+    }
+    println!("(dry run, nothing was saved)");
+
+    Ok(())
+}
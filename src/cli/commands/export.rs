@@ -0,0 +1,23 @@
+use crate::{
+    exporters::{self, ExportFormat},
+    storage::Storage,
+    tag_query::TagQuery,
+};
+use anyhow::Result;
+use std::path::PathBuf;
+
+pub fn export(format: ExportFormat, tags: Option<TagQuery>, output: Option<PathBuf>) -> Result<()> {
+    let storage = Storage::load()?;
+    let projects = storage.list_filtered(tags.as_ref());
+    let rendered = exporters::render(format, &projects)?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, rendered)?;
+            eprintln!("Exported {} projects to {}", projects.len(), path.display());
+        }
+        None => println!("{}", rendered.trim_end()),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use crate::storage::{undoable, HistoryEntry, Storage};
+use anyhow::Result;
+use chrono::Local;
+
+pub fn history(limit: Option<usize>, json: bool) -> Result<()> {
+    let storage = Storage::load()?;
+    let entries = storage.history()?;
+    let undoable = undoable(&entries);
+
+    let recent: Vec<&HistoryEntry> = entries
+        .iter()
+        .rev()
+        .take(limit.unwrap_or(usize::MAX))
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string(&recent)?);
+        return Ok(());
+    }
+
+    if recent.is_empty() {
+        println!("No history");
+        return Ok(());
+    }
+
+    for entry in recent {
+        let undone = entry.reverts == 0 && !undoable.iter().any(|e| std::ptr::eq(*e, entry));
+
+        println!(
+            "{}  {}{}",
+            entry
+                .timestamp
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S"),
+            entry.operation,
+            if undone { " (undone)" } else { "" }
+        );
+    }
+
+    Ok(())
+}
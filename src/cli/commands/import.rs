@@ -0,0 +1,122 @@
+use super::dry_run;
+use crate::{
+    git,
+    importers::ImportSource,
+    storage::{Storage, StorageError},
+    Project,
+};
+use anyhow::Result;
+use inquire::MultiSelect;
+use std::fmt;
+use std::path::PathBuf;
+
+/// A project about to be imported, shown in the selection prompt.
+struct Candidate(Project);
+
+impl fmt::Display for Candidate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} - {}", self.0, self.0.path.display())?;
+        if self.0.visits > 0 {
+            write!(f, " ({} visits)", self.0.visits)?;
+        }
+        if !self.0.tags.is_empty() {
+            write!(f, " [{}]", self.0.tags.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+pub fn import(
+    from: ImportSource,
+    file: Option<PathBuf>,
+    tags: Option<Vec<String>>,
+    git_only: bool,
+    yes: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let imported = from.parse(&from.read(file.as_deref())?)?;
+    let mut storage = Storage::load()?.dry_run(dry_run);
+
+    let mut skipped = 0;
+    let mut candidates: Vec<Candidate> = Vec::new();
+    for entry in imported {
+        let Ok(path) = std::fs::canonicalize(&entry.path) else {
+            skipped += 1;
+            continue;
+        };
+        if !path.is_dir()
+            || (git_only && !git::is_repo(&path))
+            || storage.find_by_path(&path).is_some()
+            || candidates.iter().any(|c| c.0.path == path)
+        {
+            skipped += 1;
+            continue;
+        }
+
+        let name = entry.name.unwrap_or_else(|| Project::default_name(&path));
+        let is_bare = git::is_bare_repo(&path);
+        let mut project = Project {
+            description: entry.description,
+            ..Project::new(name, path, is_bare)
+        }
+        .with_readme_description();
+        project.tags = entry.tags;
+        project.aliases = entry.aliases;
+        project.notes = entry.notes;
+        project.pinned = entry.pinned;
+        project.archived = entry.archived;
+        for tag in tags.iter().flatten() {
+            project.add_tag(tag.clone());
+        }
+        if let Some(visits) = entry.visits {
+            project.visits = visits;
+        }
+        if let Some(added_at) = entry.added_at {
+            project.added_at = added_at;
+        }
+        // Without a timestamp the project was never opened through bvo.
+        project.last_opened_at = entry.last_opened_at.unwrap_or(project.added_at);
+
+        candidates.push(Candidate(project));
+    }
+
+    if skipped > 0 {
+        let reasons = if git_only {
+            "missing, already registered or not a git repository"
+        } else {
+            "missing or already registered"
+        };
+        println!("Skipped {} entries ({})", skipped, reasons);
+    }
+
+    if candidates.is_empty() {
+        println!("Nothing to import from {}", from);
+        return Ok(());
+    }
+
+    let selected = if yes {
+        candidates
+    } else {
+        MultiSelect::new(
+            &format!("Select projects to import from {from}:"),
+            candidates,
+        )
+        .with_all_selected_by_default()
+        .prompt()?
+    };
+
+    for Candidate(project) in selected {
+        let name = project.name.clone();
+        match storage.add(project) {
+            Ok(()) => println!("Project '{}' added", name),
+            Err(
+                e @ (StorageError::DuplicateProjectName(_)
+                | StorageError::DuplicateProjectPath(_)
+                | StorageError::AliasInUse(..)),
+            ) => println!("Skipped '{}': {}", name, e),
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    dry_run::report(&storage)
+}
@@ -1,48 +1,158 @@
-use crate::storage::Storage;
+use crate::{cli::Selection, git, git::RepoStatus, Project};
 use anyhow::Result;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::path::PathBuf;
 
-pub fn list(tags: Option<Vec<String>>, json: bool) -> Result<()> {
-    let storage = Storage::load()?;
-    let tags = tags.unwrap_or_default();
-    let projects = storage.list_filtered(&tags);
-    let json_projects = serde_json::to_string(&projects)?;
+#[derive(Serialize)]
+struct WorktreeStatus {
+    name: String,
+    path: PathBuf,
+    status: Option<RepoStatus>,
+}
 
-    if projects.is_empty() {
-        if json {
-            println!("{}", json_projects);
-        } else {
-            println!("No projects found");
+#[derive(Serialize)]
+struct ProjectStatus<'a> {
+    #[serde(flatten)]
+    project: ProjectEntry<'a>,
+    /// `None` for bare repositories, whose status lives in `worktrees`.
+    status: Option<RepoStatus>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    worktrees: Vec<WorktreeStatus>,
+}
+
+/// A project as listed, with its profile when listing across profiles.
+#[derive(Serialize)]
+struct ProjectEntry<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    profile: Option<&'a str>,
+    #[serde(flatten)]
+    project: &'a Project,
+}
+
+impl<'a> From<&'a Project> for ProjectEntry<'a> {
+    fn from(project: &'a Project) -> Self {
+        Self {
+            profile: project.profile.as_deref(),
+            project,
+        }
+    }
+}
+
+impl<'a> ProjectStatus<'a> {
+    fn collect(project: &'a Project) -> Self {
+        if !project.is_bare_repo {
+            return Self {
+                project: project.into(),
+                status: git::status(&project.path).ok(),
+                worktrees: Vec::new(),
+            };
         }
 
+        let worktrees = git::list_worktrees(&project.path)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|wt| WorktreeStatus {
+                status: git::status(&wt.path).ok(),
+                name: wt.name,
+                path: wt.path,
+            })
+            .collect();
+
+        Self {
+            project: project.into(),
+            status: None,
+            worktrees,
+        }
+    }
+}
+
+pub fn list(selection: Selection, json: bool, status: bool, notes: bool) -> Result<()> {
+    let projects = selection.load()?;
+
+    // Git status is read in parallel since each repository is opened and
+    // walked independently.
+    let statuses: Vec<ProjectStatus> = if status {
+        projects.par_iter().map(ProjectStatus::collect).collect()
+    } else {
+        Vec::new()
+    };
+
+    if json {
+        let json_projects = if status {
+            serde_json::to_string(&statuses)?
+        } else {
+            let entries: Vec<ProjectEntry> = projects.iter().map(ProjectEntry::from).collect();
+            serde_json::to_string(&entries)?
+        };
+        println!("{}", json_projects);
+
+        return Ok(());
+    }
+
+    if projects.is_empty() {
+        println!("No projects found");
         return Ok(());
     }
 
-    for project in projects {
+    for (i, project) in projects.iter().enumerate() {
         let tags_str = if project.tags.is_empty() {
             String::new()
         } else {
             format!(" [{}]", project.tags.join(", "))
         };
 
-        let bare_indicator = if project.is_bare_repo { " (bare)" } else { "" };
         let broken_indicator = if project.exists() { "" } else { "!" };
+        let aliases = if project.aliases.is_empty() {
+            String::new()
+        } else {
+            format!(" (alias: {})", project.aliases.join(", "))
+        };
+        let state = match (project.pinned, project.archived) {
+            (_, true) => " (archived)",
+            (true, _) => " (pinned)",
+            _ => "",
+        };
 
-        if !json {
-            println!(
-                "{}{}{} - {}{}",
-                broken_indicator,
-                project.name,
-                bare_indicator,
-                project.path.display(),
-                tags_str
-            );
-            continue;
+        println!(
+            "{}{}{}{} - {}{}",
+            broken_indicator,
+            project,
+            aliases,
+            state,
+            project.path.display(),
+            tags_str
+        );
+
+        if let Some(description) = &project.description {
+            println!("    {description}");
+        }
+        if let Some(text) = project.notes.as_ref().filter(|_| notes) {
+            for line in text.lines() {
+                println!("    | {line}");
+            }
+        }
+        if let Some(entry) = statuses.get(i) {
+            print_status(entry);
         }
     }
 
-    if json {
-        println!("{}", json_projects);
+    Ok(())
+}
+
+fn print_status(entry: &ProjectStatus) {
+    if !entry.project.project.is_bare_repo {
+        match &entry.status {
+            Some(status) => println!("    {status}"),
+            None => println!("    (status unavailable)"),
+        }
+        return;
     }
 
-    Ok(())
+    for wt in &entry.worktrees {
+        match &wt.status {
+            Some(status) => println!("    {}: {status}", wt.name),
+            None => println!("    {}: (status unavailable)", wt.name),
+        }
+    }
 }
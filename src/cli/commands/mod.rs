@@ -1,13 +1,57 @@
 mod add;
+mod alias;
+mod archive;
+mod clone;
+mod complete;
 mod config;
+mod describe;
+mod doctor;
+mod dry_run;
+mod export;
+mod history;
+mod import;
 mod list;
+mod mv;
+mod note;
+mod path;
 mod pick;
+mod pin;
+mod preview;
+mod rank;
+mod recent;
 mod remove;
+mod rename;
+mod scan;
+mod selection;
+mod sync;
 mod tag;
+mod undo;
+mod worktree;
 
 pub use add::*;
+pub use alias::*;
+pub use archive::*;
+pub use clone::*;
+pub use complete::*;
 pub use config::*;
+pub use describe::*;
+pub use doctor::*;
+pub use export::*;
+pub use history::*;
+pub use import::*;
 pub use list::*;
+pub use mv::*;
+pub use note::*;
+pub use path::*;
 pub use pick::*;
+pub use pin::*;
+pub use preview::*;
+pub use rank::*;
+pub use recent::*;
 pub use remove::*;
+pub use rename::*;
+pub use scan::*;
+pub use sync::*;
 pub use tag::*;
+pub use undo::*;
+pub use worktree::*;
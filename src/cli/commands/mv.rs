@@ -0,0 +1,81 @@
+use super::dry_run;
+use crate::{git, storage::Storage, utils::fs::expand_home};
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub fn mv(name: String, path: PathBuf, move_files: bool, dry_run: bool) -> Result<()> {
+    let mut storage = Storage::load()?.dry_run(dry_run);
+    let old_path = storage
+        .find_by_name(&name)
+        .map(|p| p.path.clone())
+        .with_context(|| format!("project '{}' not found", name))?;
+
+    let path = expand_home(&path);
+    let new_path = if move_files {
+        if path.exists() {
+            bail!("'{}' already exists", path.display());
+        }
+        absolute_destination(&path)?
+    } else {
+        fs::canonicalize(&path).with_context(|| format!("'{}' not found", path.display()))?
+    };
+
+    if move_files && !dry_run {
+        fs::rename(&old_path, &new_path).with_context(|| {
+            format!(
+                "failed to move '{}' to '{}'",
+                old_path.display(),
+                new_path.display()
+            )
+        })?;
+    }
+
+    // Checked after the move: the repository is only there once it happened.
+    let check_path = if move_files && dry_run {
+        &old_path
+    } else {
+        &new_path
+    };
+    let is_bare = git::is_bare_repo(check_path);
+
+    if let Err(e) = storage.relocate(&name, new_path.clone(), is_bare) {
+        if move_files && !dry_run {
+            fs::rename(&new_path, &old_path)?;
+        }
+        return Err(e.into());
+    }
+
+    if move_files {
+        let verb = if dry_run { "Would move" } else { "Moved" };
+        println!(
+            "{verb} '{}' to '{}'",
+            old_path.display(),
+            new_path.display()
+        );
+    }
+    println!("Project '{}' now points to {}", name, new_path.display());
+    if is_bare {
+        println!("  (bare repository detected)");
+        if move_files {
+            println!("  run `git worktree repair` if its worktrees moved with it");
+        }
+    }
+
+    dry_run::report(&storage)
+}
+
+/// Absolute form of a destination that does not exist yet: its parent must.
+fn absolute_destination(path: &Path) -> Result<PathBuf> {
+    let file_name = path
+        .file_name()
+        .with_context(|| format!("invalid destination '{}'", path.display()))?;
+    let parent = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    let parent =
+        fs::canonicalize(parent).with_context(|| format!("'{}' not found", parent.display()))?;
+
+    Ok(parent.join(file_name))
+}
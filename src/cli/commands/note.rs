@@ -0,0 +1,40 @@
+use super::dry_run;
+use crate::{
+    storage::{Storage, StorageError},
+    utils::editor,
+};
+use anyhow::Result;
+
+pub fn note(name: String, print: bool, clear: bool, dry_run: bool) -> Result<()> {
+    let mut storage = Storage::load()?.dry_run(dry_run);
+    let project = storage
+        .find_by_name(&name)
+        .ok_or_else(|| StorageError::ProjectNotFound(name.clone()))?;
+
+    if print {
+        if let Some(notes) = &project.notes {
+            println!("{notes}");
+        }
+        return Ok(());
+    }
+
+    let notes = if clear {
+        None
+    } else {
+        let edited = editor::edit(project.notes.as_deref().unwrap_or_default(), "notes.md")?;
+        Some(edited.trim().to_string()).filter(|n| !n.is_empty())
+    };
+
+    if notes == project.notes {
+        println!("Notes of '{name}' unchanged");
+        return Ok(());
+    }
+
+    storage.update(&name, |project| project.notes = notes.clone())?;
+    match notes {
+        Some(_) => println!("Notes of '{name}' saved"),
+        None => println!("Notes of '{name}' cleared"),
+    }
+
+    dry_run::report(&storage)
+}
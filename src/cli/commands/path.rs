@@ -0,0 +1,59 @@
+use crate::{
+    git::{self, Worktree},
+    resolve::{find_worktree, resolve, Resolution, EXIT_AMBIGUOUS, EXIT_NOT_FOUND},
+    storage::Storage,
+    tag_query::TagQuery,
+    Project,
+};
+use anyhow::{bail, Result};
+
+pub fn path(
+    query: String,
+    tags: Option<TagQuery>,
+    worktree: Option<String>,
+    first: bool,
+) -> Result<()> {
+    let storage = Storage::load()?;
+    let projects = storage.list_filtered(tags.as_ref());
+
+    let project = match resolve(&projects, &query) {
+        Resolution::Found(project) => project,
+        Resolution::Ambiguous(candidates) if first => candidates[0],
+        Resolution::Ambiguous(candidates) => {
+            eprintln!("'{}' matches more than one project:", query);
+            for project in candidates {
+                eprintln!("  {}", project.name);
+            }
+            std::process::exit(EXIT_AMBIGUOUS);
+        }
+        Resolution::NotFound => {
+            eprintln!("No project matches '{}'", query);
+            std::process::exit(EXIT_NOT_FOUND);
+        }
+    };
+
+    let path = match worktree {
+        Some(worktree) => project_worktree(project, &worktree)?.path,
+        None => project.path.clone(),
+    };
+
+    println!("{}", path.display());
+
+    Ok(())
+}
+
+/// The worktree of bare `project` matching `query` by name or branch. Exits
+/// with [`EXIT_NOT_FOUND`] when there is none.
+pub(super) fn project_worktree(project: &Project, query: &str) -> Result<Worktree> {
+    if !project.is_bare_repo {
+        bail!("project '{}' is not a bare repository", project.name);
+    }
+
+    match find_worktree(git::list_worktrees(&project.path)?, query) {
+        Some(wt) => Ok(wt),
+        None => {
+            eprintln!("No worktree of '{}' matches '{}'", project.name, query);
+            std::process::exit(EXIT_NOT_FOUND);
+        }
+    }
+}
@@ -1,23 +1,170 @@
-use crate::{fuzzy_scorer, git, git::Worktree, storage::Storage, Project};
+use super::{path::project_worktree, preview::project_preview};
+use crate::{
+    cli::Selection,
+    config::Config,
+    fuzzy_scorer, git,
+    git::Worktree,
+    profile,
+    ranking::Ranking,
+    resolve::{find_exact, resolve, EXIT_AMBIGUOUS, EXIT_NOT_FOUND},
+    storage::Storage,
+    utils::picker::{pick_external, pick_with_preview},
+    Project,
+};
 use anyhow::Result;
+use chrono::Utc;
 use inquire::{
     ui::{RenderConfig, Styled},
-    Select,
+    InquireError, Select,
 };
+use std::{cell::LazyCell, fmt};
 
-pub fn pick(query: Option<String>, tags: Option<Vec<String>>) -> Result<()> {
-    let mut storage = Storage::load()?;
+/// A project, or one worktree of a bare project, offered by the picker.
+#[derive(Clone)]
+struct Candidate {
+    project: Project,
+    worktree: Option<Worktree>,
+}
 
-    let projects: Vec<Project> = match tags {
-        None => storage.list().into_iter().cloned().collect(),
-        Some(tags) => storage.list_filtered(&tags).into_iter().cloned().collect(),
-    };
+impl From<Project> for Candidate {
+    fn from(project: Project) -> Self {
+        Self {
+            project,
+            worktree: None,
+        }
+    }
+}
+
+impl fmt::Display for Candidate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.worktree {
+            Some(worktree) => {
+                if let Some(profile) = &self.project.profile {
+                    write!(f, "{profile}:")?;
+                }
+                write!(f, "{}/{worktree}", self.project.name)
+            }
+            None => write!(f, "{}", self.project),
+        }
+    }
+}
 
+impl Candidate {
+    /// `project` or `project/worktree`, as typed to pick it exactly.
+    fn name(&self) -> String {
+        match &self.worktree {
+            Some(worktree) => format!("{}/{}", self.project.name, worktree.name),
+            None => self.project.name.clone(),
+        }
+    }
+
+    fn search_text(&self) -> String {
+        match &self.worktree {
+            Some(worktree) => format!(
+                "{}/{worktree} {}",
+                self.project.name,
+                self.project.search_text()
+            ),
+            None => self.project.search_text(),
+        }
+    }
+}
+
+/// `projects` in order, each bare one followed by its worktrees, highest
+/// frecency first.
+fn with_worktrees(projects: &[Project]) -> Vec<Candidate> {
+    let mut candidates = Vec::new();
+    for project in projects {
+        candidates.push(Candidate::from(project.clone()));
+        if !project.is_bare_repo {
+            continue;
+        }
+
+        let mut worktrees = git::list_worktrees(&project.path).unwrap_or_default();
+        rank_worktrees(project, &mut worktrees);
+        candidates.extend(worktrees.into_iter().map(|worktree| Candidate {
+            project: project.clone(),
+            worktree: Some(worktree),
+        }));
+    }
+
+    candidates
+}
+
+/// Sorts `worktrees` of `project` by frecency, keeping git's order among
+/// those never opened, and returns the position of the most recently opened.
+fn rank_worktrees(project: &Project, worktrees: &mut Vec<Worktree>) -> Option<usize> {
+    let (ranking, now) = (Ranking::current(), Utc::now());
+    let mut keyed: Vec<(f64, Worktree)> = worktrees
+        .drain(..)
+        .map(|wt| (project.worktree_frecency(&wt.name, &ranking, now), wt))
+        .collect();
+    keyed.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+    worktrees.extend(keyed.into_iter().map(|(_, wt)| wt));
+
+    let last = project.last_worktree()?;
+    worktrees.iter().position(|wt| wt.name == last)
+}
+
+/// The worktree `--first` opens without prompting: the only one, or else the
+/// one opened most recently.
+fn default_worktree<'a>(project: &Project, worktrees: &'a [Worktree]) -> Option<&'a Worktree> {
+    if let [only] = worktrees {
+        return Some(only);
+    }
+
+    let last = project.last_worktree()?;
+    worktrees.iter().find(|wt| wt.name == last)
+}
+
+/// The candidate `query` names exactly: a project by name or alias, or one
+/// worktree as `project/worktree`. `candidates` is only called for the latter,
+/// so a jump by name never lists worktrees.
+fn find_exact_candidate<'a>(
+    projects: &[Project],
+    query: &str,
+    candidates: impl FnOnce() -> &'a [Candidate],
+) -> Option<Candidate> {
+    if let Some(project) = find_exact(projects.iter(), query) {
+        return Some(Candidate::from(project.clone()));
+    }
+    if !query.contains('/') {
+        return None;
+    }
+
+    candidates().iter().find(|c| c.name() == query).cloned()
+}
+
+pub fn pick(
+    query: Option<String>,
+    selection: Selection,
+    preview: bool,
+    first: bool,
+    worktree: Option<String>,
+    search_notes: bool,
+) -> Result<()> {
+    let config = Config::load()?;
+    let preview = preview || config.picker_preview.unwrap_or(false);
+    let search_notes = search_notes || config.picker_search_notes.unwrap_or(false);
+    let all_profiles = selection.all_profiles;
+
+    let projects = selection.load()?;
     if projects.is_empty() {
         eprintln!("No projects available");
         std::process::exit(1);
     }
 
+    // Listing worktrees opens every bare repository, so it waits until a
+    // query needs fuzzy matching or the prompt. They are redundant when
+    // `--worktree` names one already.
+    let candidates = LazyCell::new(|| {
+        if worktree.is_none() && config.picker_worktrees.unwrap_or(true) {
+            with_worktrees(&projects)
+        } else {
+            projects.iter().cloned().map(Candidate::from).collect()
+        }
+    });
+
     if projects.iter().any(|p| !p.exists()) {
         let binary = env!("CARGO_BIN_NAME");
 
@@ -25,66 +172,331 @@ pub fn pick(query: Option<String>, tags: Option<Vec<String>>) -> Result<()> {
         println!("run `{binary} list` to show broken projects")
     }
 
-    fuzzy_scorer!(fuzzy_project_scorer, Project);
+    fuzzy_scorer!(
+        fuzzy_candidate_scorer,
+        Candidate,
+        |c: &Candidate, label: &str| {
+            if search_notes {
+                format!("{label} {}", c.search_text())
+            } else {
+                label.to_string()
+            }
+        }
+    );
+
+    let prompt_selection = |q: Option<String>| -> Result<Candidate> {
+        if let Some(command) = config.external_picker() {
+            // The built-in preview gets each candidate's key from a hidden
+            // first field, whatever `picker_format` shows.
+            let keyed = preview && config.picker_preview_command.is_none();
+            let lines: Vec<String> = candidates
+                .iter()
+                .map(|c| external_line(config.picker_format(), c, keyed))
+                .collect();
+            // Keys across profiles are `profile:name`, which preview resolves
+            // itself; otherwise pin the profile of this run.
+            let preview_profile = if all_profiles {
+                String::new()
+            } else {
+                format!(" --profile {}", profile::active())
+            };
+            let preview_command = config.picker_preview_command.clone().or_else(|| {
+                preview
+                    .then(|| format!("{}{preview_profile} preview {{1}}", env!("CARGO_BIN_NAME")))
+            });
+            let command = if keyed {
+                format!("{command} --delimiter '\t' --with-nth 2..")
+            } else {
+                command.to_string()
+            };
+
+            return match pick_external(
+                &command,
+                &lines,
+                &q.unwrap_or_default(),
+                preview_command.as_deref(),
+            )? {
+                Some(index) => Ok(candidates[index].clone()),
+                None => Err(InquireError::OperationCanceled.into()),
+            };
+        }
+
+        if preview {
+            return pick_with_preview(
+                "Select a project:",
+                candidates.clone(),
+                &q.unwrap_or_default(),
+                search_notes.then_some(Candidate::search_text as fn(&Candidate) -> String),
+                candidate_preview,
+            )?
+            .ok_or_else(|| InquireError::OperationCanceled.into());
+        }
 
-    let prompt_project_selection = |projects: &[Project], q: Option<String>| {
-        Select::new("Select a project:", projects.to_vec())
+        Ok(Select::new("Select a project:", candidates.clone())
             .with_starting_filter_input(&q.unwrap_or_default())
             .with_vim_mode(false)
-            .with_scorer(&fuzzy_project_scorer)
+            .with_scorer(&fuzzy_candidate_scorer)
             .with_render_config(
                 RenderConfig::default_colored()
                     .with_scroll_up_prefix(Styled::new("↑"))
                     .with_scroll_down_prefix(Styled::new("↓")),
             )
-            .prompt()
+            .prompt()?)
     };
 
-    let project = if let Some(ref q) = query {
-        let names: Vec<String> = projects.iter().map(|p| p.name.clone()).collect();
+    let selected = if first {
+        let projects: Vec<&Project> = projects.iter().collect();
+        let best = match query {
+            Some(ref q) => resolve(&projects, q).first(),
+            None => projects.first().copied(),
+        };
+
+        match best {
+            Some(project) => Candidate::from(project.clone()),
+            None => {
+                eprintln!("No project matches '{}'", query.unwrap_or_default());
+                std::process::exit(EXIT_NOT_FOUND);
+            }
+        }
+    } else if let Some(exact) = query
+        .as_deref()
+        .and_then(|q| find_exact_candidate(&projects, q, || &candidates))
+    {
+        exact
+    } else if let Some(ref q) = query {
+        let names: Vec<String> = candidates
+            .iter()
+            .map(|c| {
+                if search_notes {
+                    c.search_text()
+                } else {
+                    c.name()
+                }
+            })
+            .collect();
         let fuzzy_filtered = frizbee::match_list_indices(q, &names, &frizbee::Config::default());
-        let pre_filtered: Vec<&Project> = fuzzy_filtered
+        let pre_filtered: Vec<&Candidate> = fuzzy_filtered
             .iter()
-            .filter_map(|m| projects.get(m.index as usize))
+            .filter_map(|m| candidates.get(m.index as usize))
             .collect();
 
         if pre_filtered.len() == 1 {
             pre_filtered[0].clone()
         } else if pre_filtered.len() > 1 {
-            prompt_project_selection(&projects, Some(q.clone()))?
+            prompt_selection(Some(q.clone()))?
         } else {
-            prompt_project_selection(&projects, None)?
+            prompt_selection(None)?
         }
     } else {
-        prompt_project_selection(&projects, None)?
+        prompt_selection(None)?
     };
+    let Candidate {
+        project,
+        worktree: picked,
+    } = selected;
 
     // If bare repo, show available worktrees
-    let final_path = if project.is_bare_repo {
+    let worktree = if picked.is_some() {
+        picked
+    } else if let Some(ref worktree) = worktree {
+        Some(project_worktree(&project, worktree)?)
+    } else if project.is_bare_repo && first {
+        let worktrees = git::list_worktrees(&project.path).unwrap_or_default();
+        match default_worktree(&project, &worktrees) {
+            Some(worktree) => Some(worktree.clone()),
+            None if worktrees.is_empty() => None,
+            None => {
+                eprintln!("'{}' has more than one worktree:", project.name);
+                for worktree in worktrees {
+                    eprintln!("  {worktree}");
+                }
+                std::process::exit(EXIT_AMBIGUOUS);
+            }
+        }
+    } else if project.is_bare_repo {
         match git::list_worktrees(&project.path) {
-            Ok(worktrees) if worktrees.len() == 1 => worktrees.first().unwrap().path.clone(),
-            Ok(worktrees) if !worktrees.is_empty() => {
+            Ok(worktrees) if worktrees.len() == 1 => worktrees.into_iter().next(),
+            Ok(mut worktrees) if !worktrees.is_empty() && config.external_picker().is_some() => {
+                rank_worktrees(&project, &mut worktrees);
+                let command = config.external_picker().unwrap_or_default();
+                let lines: Vec<String> = worktrees.iter().map(|wt| wt.to_string()).collect();
+
+                pick_external(command, &lines, "", None)?.map(|index| worktrees[index].clone())
+            }
+            Ok(mut worktrees) if !worktrees.is_empty() => {
+                let last_used = rank_worktrees(&project, &mut worktrees);
                 fuzzy_scorer!(fuzzy_worktree_scorer, Worktree);
 
                 let wt_selection = Select::new("Select a worktree:", worktrees)
                     .with_scorer(&fuzzy_worktree_scorer)
+                    .with_starting_cursor(last_used.unwrap_or_default())
                     .with_help_message("<ESC> to skip this and navigate to the project root")
                     .with_vim_mode(false);
 
                 match wt_selection.prompt_skippable() {
-                    Ok(Some(selected)) => selected.path.clone(),
+                    Ok(selected) => selected,
                     Err(_) => std::process::exit(1),
-                    _ => project.path.clone(),
                 }
             }
-            _ => project.path.clone(),
+            _ => None,
         }
     } else {
-        project.path.clone()
+        None
     };
 
-    storage.update_access(&project.name)?;
+    let mut storage = match &project.profile {
+        Some(profile) => Storage::load_profile(profile)?,
+        None => Storage::load()?,
+    };
+    storage.update_access(&project.name, worktree.as_ref().map(|wt| wt.name.as_str()))?;
+    let final_path = worktree.as_ref().map_or(&project.path, |wt| &wt.path);
     println!("{}", final_path.display());
 
     Ok(())
 }
+
+/// Preview of a candidate: the worktree, if any, above its project's preview.
+fn candidate_preview(candidate: &Candidate) -> Vec<String> {
+    let mut lines = Vec::new();
+    if let Some(worktree) = &candidate.worktree {
+        lines.push(format!("Worktree: {worktree}"));
+        lines.push(format!("  {}", worktree.path.display()));
+        if let Ok(status) = git::status(&worktree.path) {
+            lines.push(format!("  {status}"));
+        }
+        lines.push(String::new());
+    }
+    lines.extend(project_preview(&candidate.project));
+
+    lines
+}
+
+/// Line handed to an external picker. With `keyed`, it is prefixed with the
+/// candidate's key and a tab, for pickers told to hide the first field.
+fn external_line(format: &str, candidate: &Candidate, keyed: bool) -> String {
+    let line = format_candidate(format, candidate);
+    if !keyed {
+        return line;
+    }
+
+    match &candidate.project.profile {
+        Some(profile) => format!("{profile}:{}\t{line}", candidate.name()),
+        None => format!("{}\t{line}", candidate.name()),
+    }
+}
+
+/// Expands a `picker_format` template for `candidate`. Supports `{name}`,
+/// `{path}`, `{tags}`, `{bare}` and `{description}`; for worktrees `{name}` is
+/// `project/worktree [branch]` and `{path}` the worktree's. Projects listed
+/// across profiles are prefixed with `profile:`.
+fn format_candidate(format: &str, candidate: &Candidate) -> String {
+    let project = &candidate.project;
+    let (name, path, bare) = match &candidate.worktree {
+        Some(worktree) => (format!("{}/{worktree}", project.name), &worktree.path, ""),
+        None => (
+            project.name.clone(),
+            &project.path,
+            if project.is_bare_repo { "(bare)" } else { "" },
+        ),
+    };
+    let line = format
+        .replace("{name}", &name)
+        .replace("{path}", &path.to_string_lossy())
+        .replace("{tags}", &project.tags.join(","))
+        .replace("{bare}", bare)
+        .replace(
+            "{description}",
+            project.description.as_deref().unwrap_or_default(),
+        );
+
+    match &project.profile {
+        Some(profile) => format!("{profile}:{line}"),
+        None => line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::Cell, path::PathBuf};
+
+    fn worktree(name: &str) -> Worktree {
+        Worktree {
+            name: name.to_string(),
+            path: PathBuf::from("/tmp/repo").join(name),
+            branch: Some(name.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_external_line_keys_any_format() {
+        let mut project = Project::new("api".to_string(), PathBuf::from("/srv/api"), true)
+            .with_tags(vec!["work".to_string()]);
+        let format = "{name} {path} [{tags}]";
+        let candidate = Candidate::from(project.clone());
+        assert_eq!(
+            external_line(format, &candidate, false),
+            "api /srv/api [work]"
+        );
+        assert_eq!(
+            external_line(format, &candidate, true),
+            "api\tapi /srv/api [work]"
+        );
+
+        project.profile = Some("work".to_string());
+        let worktree = Candidate {
+            project,
+            worktree: Some(worktree("main")),
+        };
+        let line = external_line(format, &worktree, true);
+        assert_eq!(line.split_once('\t').unwrap().0, "work:api/main");
+    }
+
+    #[test]
+    fn test_default_worktree() {
+        let mut project = Project::new("repo".to_string(), PathBuf::from("/tmp/repo"), true);
+        let only = [worktree("main")];
+        assert_eq!(default_worktree(&project, &only).unwrap().name, "main");
+
+        let worktrees = [worktree("main"), worktree("feature")];
+        assert!(default_worktree(&project, &worktrees).is_none());
+
+        project.on_worktree_access("feature");
+        assert_eq!(
+            default_worktree(&project, &worktrees).unwrap().name,
+            "feature"
+        );
+
+        project.on_worktree_access("removed");
+        assert!(default_worktree(&project, &worktrees).is_none());
+    }
+
+    #[test]
+    fn test_alias_jump_does_not_list_worktrees() {
+        let mut bare = Project::new("repo".to_string(), PathBuf::from("/tmp/repo"), true);
+        bare.aliases = vec!["r".to_string()];
+        let worktree = Candidate {
+            project: bare.clone(),
+            worktree: Some(worktree("main")),
+        };
+        let candidates = vec![Candidate::from(bare.clone()), worktree];
+        let projects = vec![bare];
+
+        let listed = Cell::new(false);
+        let list = || {
+            listed.set(true);
+            candidates.as_slice()
+        };
+
+        let picked = find_exact_candidate(&projects, "r", list).unwrap();
+        assert!(picked.worktree.is_none());
+        assert!(!listed.get());
+
+        assert!(find_exact_candidate(&projects, "nope", list).is_none());
+        assert!(!listed.get());
+
+        let picked = find_exact_candidate(&projects, "repo/main", list).unwrap();
+        assert_eq!(picked.name(), "repo/main");
+        assert!(listed.get());
+    }
+}
@@ -0,0 +1,17 @@
+use super::dry_run;
+use crate::storage::Storage;
+use anyhow::Result;
+
+/// Pins (`pinned = true`) or unpins a project.
+pub fn pin(name: String, pinned: bool, dry_run: bool) -> Result<()> {
+    let mut storage = Storage::load()?.dry_run(dry_run);
+    storage.update(&name, |project| project.pinned = pinned)?;
+
+    if pinned {
+        println!("Project '{name}' pinned");
+    } else {
+        println!("Project '{name}' unpinned");
+    }
+
+    dry_run::report(&storage)
+}
@@ -0,0 +1,77 @@
+use crate::{git, profile, storage::Storage, storage::StorageError, Project};
+use anyhow::Result;
+/// Number of README lines shown in the preview pane.
+const README_PREVIEW_LINES: usize = 12;
+
+pub fn preview(name: String) -> Result<()> {
+    // `profile:name` comes from pickers listing every profile.
+    let (storage, name) = match name.split_once(':') {
+        Some((prefix, rest)) if profile::all()?.iter().any(|p| p == prefix) => {
+            (Storage::load_profile(prefix)?, rest.to_string())
+        }
+        _ => (Storage::load()?, name),
+    };
+    // Worktree candidates are keyed `project/worktree`.
+    let project = storage
+        .find_by_name(&name)
+        .or_else(|| {
+            let (project, _) = name.split_once('/')?;
+            storage.find_by_name(project)
+        })
+        .ok_or(StorageError::ProjectNotFound(name))?;
+
+    for line in project_preview(project) {
+        println!("{}", line);
+    }
+
+    Ok(())
+}
+
+/// Lines shown in the preview pane for the highlighted project.
+pub(super) fn project_preview(project: &Project) -> Vec<String> {
+    let tags = if project.tags.is_empty() {
+        "-".to_string()
+    } else {
+        project.tags.join(", ")
+    };
+
+    let mut lines = Vec::new();
+    if let Some(description) = &project.description {
+        lines.push(description.clone());
+        lines.push(String::new());
+    }
+    lines.extend([
+        format!("Path:    {}", project.path.display()),
+        format!("Tags:    {}", tags),
+        format!("Visits:  {}", project.visits),
+        format!(
+            "Opened:  {}",
+            project.last_opened_at.format("%Y-%m-%d %H:%M")
+        ),
+    ]);
+
+    if project.is_bare_repo {
+        lines.push("Worktrees:".to_string());
+        for wt in git::list_worktrees(&project.path).unwrap_or_default() {
+            match git::status(&wt.path) {
+                Ok(status) => lines.push(format!("  {}: {}", wt.name, status)),
+                Err(_) => lines.push(format!("  {}", wt)),
+            }
+        }
+    } else if let Ok(status) = git::status(&project.path) {
+        lines.push(format!("Git:     {}", status));
+    }
+
+    if let Some(notes) = &project.notes {
+        lines.push(String::new());
+        lines.push("Notes:".to_string());
+        lines.extend(notes.lines().map(|line| format!("  {line}")));
+    }
+
+    if let Some(readme) = project.readme() {
+        lines.push(String::new());
+        lines.extend(readme.lines().take(README_PREVIEW_LINES).map(String::from));
+    }
+
+    lines
+}
@@ -0,0 +1,71 @@
+use crate::{cli::Selection, ranking::Ranking};
+use anyhow::Result;
+use chrono::{TimeDelta, Utc};
+
+pub fn rank(selection: Selection, explain: bool) -> Result<()> {
+    let projects = selection.load()?;
+    let ranking = Ranking::current();
+    let now = Utc::now();
+
+    if projects.is_empty() {
+        println!("No projects");
+        return Ok(());
+    }
+
+    if explain {
+        println!(
+            "{:>9}  {:>6}  {:>8}  {:>6}  {:>7}  {:>7}  NAME",
+            "SCORE", "VISITS", "OPENED", "DECAY", "HISTORY", "CONTEXT"
+        );
+    }
+
+    for project in &projects {
+        let score = ranking.score(project, now);
+        let pinned = if project.pinned { " (pinned)" } else { "" };
+
+        if explain {
+            println!(
+                "{:>9.2}  {:>6}  {:>8}  {:>6.3}  {:>7.2}  {:>7.2}  {project}{pinned}",
+                score.value,
+                score.visits,
+                format_age(score.age),
+                score.decay,
+                score.history,
+                score.context,
+            );
+        } else {
+            println!("{:>9.2}  {project}{pinned}", score.value);
+        }
+    }
+
+    if explain {
+        let total: u64 = projects.iter().map(|p| u64::from(p.visits)).sum();
+        println!();
+        println!("score = ((1 + unlogged visits) * decay + history) * context");
+        println!(
+            "a visit made d days ago weighs 0.5^(d / {}); history sums the logged ones",
+            ranking.half_life_days
+        );
+        if ranking.context {
+            println!(
+                "context = 1 + share of logged visits made around this time on this kind of day"
+            );
+        }
+        println!("pinned projects rank first regardless of score");
+        println!(
+            "visits are aged once they total over {} (now {total})",
+            ranking.max_visits
+        );
+    }
+
+    Ok(())
+}
+
+/// Compact age such as `45m ago`, `3h ago` or `12d ago`.
+fn format_age(age: TimeDelta) -> String {
+    match age {
+        a if a < TimeDelta::hours(1) => format!("{}m ago", a.num_minutes()),
+        a if a < TimeDelta::days(1) => format!("{}h ago", a.num_hours()),
+        a => format!("{}d ago", a.num_days()),
+    }
+}
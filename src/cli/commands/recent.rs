@@ -0,0 +1,62 @@
+use crate::cli::Selection;
+use anyhow::Result;
+use chrono::{DateTime, Local, Utc};
+use serde::Serialize;
+use std::cmp::Reverse;
+
+#[derive(Serialize)]
+struct Visit<'a> {
+    opened_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    profile: Option<&'a str>,
+    project: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    worktree: Option<&'a str>,
+}
+
+pub fn recent(selection: Selection, limit: usize, json: bool) -> Result<()> {
+    let projects = selection.load()?;
+
+    let mut visits: Vec<Visit> = projects
+        .iter()
+        .flat_map(|project| {
+            project.visit_log.recent().map(move |opened_at| Visit {
+                opened_at,
+                profile: project.profile.as_deref(),
+                project: &project.name,
+                worktree: project
+                    .worktree_visits
+                    .iter()
+                    .find(|(_, log)| log.recent().any(|t| t == opened_at))
+                    .map(|(name, _)| name.as_str()),
+            })
+        })
+        .collect();
+    visits.sort_by_key(|v| Reverse(v.opened_at));
+    visits.truncate(limit);
+
+    if json {
+        println!("{}", serde_json::to_string(&visits)?);
+        return Ok(());
+    }
+
+    if visits.is_empty() {
+        println!("No visits recorded");
+        return Ok(());
+    }
+
+    for visit in visits {
+        let profile = visit.profile.map(|p| format!("{p}:")).unwrap_or_default();
+        let worktree = visit.worktree.map(|w| format!("/{w}")).unwrap_or_default();
+        println!(
+            "{}  {profile}{}{worktree}",
+            visit
+                .opened_at
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M"),
+            visit.project,
+        );
+    }
+
+    Ok(())
+}
@@ -1,12 +1,18 @@
-use crate::{fuzzy_scorer, storage::Storage, Project};
+use super::dry_run;
+use crate::{fuzzy_scorer, storage::Storage, tag_query::TagQuery, Project};
 use anyhow::Result;
 use inquire::{Confirm, Select};
 
-pub fn remove(name: Option<String>, tags: Option<Vec<String>>, all: bool) -> Result<()> {
-    let mut storage = Storage::load()?;
+pub fn remove(
+    name: Option<String>,
+    tags: Option<TagQuery>,
+    all: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let mut storage = Storage::load()?.dry_run(dry_run);
 
     if all {
-        if !Confirm::new("Do you really want to remove all projects?").prompt()? {
+        if !dry_run && !Confirm::new("Do you really want to remove all projects?").prompt()? {
             println!("operation aborted by the user.");
             return Ok(());
         }
@@ -17,16 +23,18 @@ pub fn remove(name: Option<String>, tags: Option<Vec<String>>, all: bool) -> Res
                     println!("Project {} removed", &project.name);
                 }
 
-                return Ok(storage.remove_all()?);
+                storage.remove_all()?;
             }
             Some(tags) => {
-                for project in storage.list_filtered(&tags) {
+                for project in storage.list_filtered(Some(&tags)) {
                     println!("Project {} removed", &project.name);
                 }
 
-                return Ok(storage.remove_all_filtered(&tags)?);
+                storage.remove_all_filtered(&tags)?;
             }
         }
+
+        return dry_run::report(&storage);
     }
 
     let name = match name {
@@ -50,5 +58,5 @@ pub fn remove(name: Option<String>, tags: Option<Vec<String>>, all: bool) -> Res
     storage.remove(&name)?;
     println!("Project '{}' removed", name);
 
-    Ok(())
+    dry_run::report(&storage)
 }
@@ -0,0 +1,12 @@
+use super::dry_run;
+use crate::storage::Storage;
+use anyhow::Result;
+
+pub fn rename(name: String, new_name: String, dry_run: bool) -> Result<()> {
+    let mut storage = Storage::load()?.dry_run(dry_run);
+    storage.rename(&name, &new_name)?;
+
+    println!("Project '{}' renamed to '{}'", name, new_name);
+
+    dry_run::report(&storage)
+}
@@ -0,0 +1,61 @@
+use super::dry_run;
+use crate::{
+    git,
+    storage::{Storage, StorageError},
+    Project,
+};
+use anyhow::Result;
+use inquire::MultiSelect;
+use std::path::PathBuf;
+
+pub fn scan(
+    root: PathBuf,
+    depth: usize,
+    tags: Option<Vec<String>>,
+    ignore: Vec<String>,
+    yes: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let root = std::fs::canonicalize(&root)?;
+    let mut storage = Storage::load()?.dry_run(dry_run);
+
+    let candidates: Vec<_> = git::discover_repositories(&root, Some(depth), &ignore)?
+        .into_iter()
+        .filter(|repo| storage.find_by_path(&repo.path).is_none())
+        .collect();
+
+    if candidates.is_empty() {
+        println!("No unregistered repositories found");
+        return Ok(());
+    }
+
+    let selected = if yes {
+        candidates
+    } else {
+        MultiSelect::new("Select repositories to register:", candidates)
+            .with_all_selected_by_default()
+            .prompt()?
+    };
+
+    for repo in selected {
+        let name = Project::default_name(&repo.path);
+        let mut project =
+            Project::new(name.clone(), repo.path, repo.is_bare).with_readme_description();
+
+        if let Some(tags) = &tags {
+            project = project.with_tags(tags.clone());
+        }
+
+        match storage.add(project) {
+            Ok(()) => println!("Project '{}' added", name),
+            Err(
+                e @ (StorageError::DuplicateProjectName(_)
+                | StorageError::DuplicateProjectPath(_)
+                | StorageError::AliasInUse(..)),
+            ) => println!("Skipped '{}': {}", name, e),
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    dry_run::report(&storage)
+}
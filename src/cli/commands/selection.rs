@@ -0,0 +1,24 @@
+use crate::{
+    cli::Selection,
+    storage::{filter_ranked, Storage},
+    Project,
+};
+use anyhow::Result;
+
+impl Selection {
+    /// Projects to offer, highest ranked first.
+    pub fn load(&self) -> Result<Vec<Project>> {
+        // Unranked here: `filter_ranked` scores them once, below.
+        let projects = if self.all_profiles {
+            Storage::load_all()?
+        } else {
+            Storage::load()?.into_projects()
+        };
+
+        Ok(filter_ranked(&projects, self.tags.as_ref())
+            .into_iter()
+            .filter(|p| self.include_archived || !p.archived)
+            .cloned()
+            .collect())
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{
+    config::Config,
+    profile::{self, DEFAULT_PROFILE},
+    storage::Storage,
+    sync::{self, MergePlan, SyncRepo, CONFIG_FILE, DEFAULT_BRANCH, REGISTRY_FILE},
+};
+use anyhow::{Context, Result};
+
+pub fn sync(remote: Option<String>) -> Result<()> {
+    let mut config = Config::load()?;
+    if let Some(remote) = remote {
+        config.set("sync_remote", &remote)?;
+        config.save()?;
+    }
+    let remote = config
+        .sync_remote
+        .clone()
+        .context("No sync remote configured, run `bvo sync --remote <url>` first")?;
+    let share_config = config.sync_config.unwrap_or(false);
+
+    let mut storage = Storage::load()?;
+    let branch = match profile::active() {
+        DEFAULT_PROFILE => DEFAULT_BRANCH.to_string(),
+        name => format!("profile/{name}"),
+    };
+    let repo = SyncRepo::init(&Storage::path(), &remote, &branch)?;
+    repo.commit_file(
+        REGISTRY_FILE,
+        &storage.registry_json()?,
+        "Record local changes",
+    )?;
+    if share_config {
+        repo.commit_file(
+            CONFIG_FILE,
+            &shared_config(&config)?,
+            "Record config changes",
+        )?;
+    }
+
+    let Some(theirs) = repo.fetch()? else {
+        repo.push()?;
+        println!("Pushed registry to empty remote {remote}");
+        return Ok(());
+    };
+    let MergePlan::Merge { base } = repo.plan(theirs)? else {
+        repo.push()?;
+        println!("Registry synced with {remote}");
+        return Ok(());
+    };
+
+    let read = |oid, file| -> Result<Option<String>> { repo.read_file(oid, file) };
+    let their_projects = match read(theirs, REGISTRY_FILE)? {
+        Some(content) => storage.parse_registry(&content)?,
+        None => Vec::new(),
+    };
+    let base_projects = match base.map(|b| read(b, REGISTRY_FILE)).transpose()?.flatten() {
+        Some(content) => Some(storage.parse_registry(&content)?),
+        None => None,
+    };
+    storage.replace_with("merge synced changes", |ours| {
+        sync::merge(base_projects.as_deref(), ours, &their_projects)
+    })?;
+
+    let registry = storage.registry_json()?;
+    let mut files = vec![(REGISTRY_FILE, registry.as_str())];
+    let merged_config;
+    if share_config {
+        let base_config = base.map(|b| read(b, CONFIG_FILE)).transpose()?.flatten();
+        merged_config = merge_config(&mut config, base_config, read(theirs, CONFIG_FILE)?)?;
+        files.push((CONFIG_FILE, merged_config.as_str()));
+    }
+    repo.commit_merge(&files, theirs)?;
+    repo.push()?;
+
+    println!("Merged remote changes and synced with {remote}");
+    Ok(())
+}
+
+fn shared_config(config: &Config) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&config.shared())?)
+}
+
+/// Config has no field-level merge: remote changes win only when the local
+/// config is unchanged since the last sync. Returns the content to commit.
+fn merge_config(
+    config: &mut Config,
+    base: Option<String>,
+    theirs: Option<String>,
+) -> Result<String> {
+    let ours = shared_config(config)?;
+
+    if let Some(theirs) = theirs {
+        if theirs != ours {
+            if base.is_none() || base.as_deref() == Some(ours.as_str()) {
+                config.adopt_shared(serde_json::from_str(&theirs)?);
+                config.save()?;
+                println!("Updated config from remote");
+            } else if base.as_deref() != Some(theirs.as_str()) {
+                eprintln!("Config changed here and on the remote, keeping the local one");
+            }
+        }
+    }
+
+    shared_config(config)
+}
@@ -1,9 +1,15 @@
+use super::dry_run;
 use crate::storage::Storage;
 use anyhow::Result;
 use inquire::Select;
 
-pub fn tag(project_name: Option<String>, tags: Vec<String>, remove: bool) -> Result<()> {
-    let mut storage = Storage::load()?;
+pub fn tag(
+    project_name: Option<String>,
+    tags: Vec<String>,
+    remove: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let mut storage = Storage::load()?.dry_run(dry_run);
 
     let project_name = match project_name {
         Some(name) => name,
@@ -27,5 +33,5 @@ pub fn tag(project_name: Option<String>, tags: Vec<String>, remove: bool) -> Res
     let action = if remove { "removed from" } else { "added to" };
     println!("Tags {} '{}'", action, project_name);
 
-    Ok(())
+    dry_run::report(&storage)
 }
@@ -0,0 +1,29 @@
+use super::dry_run;
+use crate::storage::{Storage, StorageError};
+use anyhow::Result;
+use chrono::Local;
+
+pub fn undo(count: usize, dry_run: bool) -> Result<()> {
+    let mut storage = Storage::load()?.dry_run(dry_run);
+
+    let reverted = match storage.undo(count) {
+        Err(StorageError::NothingToUndo) => {
+            println!("Nothing to undo");
+            return Ok(());
+        }
+        result => result?,
+    };
+
+    for entry in &reverted {
+        println!(
+            "Reverted '{}' from {}",
+            entry.operation,
+            entry
+                .timestamp
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S")
+        );
+    }
+
+    dry_run::report(&storage)
+}
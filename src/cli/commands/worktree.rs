@@ -0,0 +1,96 @@
+use crate::{cli::WorktreeAction, config::Config, git, storage::StorageError, Project, Storage};
+use anyhow::{bail, Result};
+use inquire::Select;
+
+pub fn worktree(action: WorktreeAction) -> Result<()> {
+    match action {
+        WorktreeAction::Add {
+            branch,
+            project,
+            base,
+        } => {
+            let project = resolve_bare_project(project)?;
+            let config = Config::load()?;
+            let path = config.worktree_path(&project.path, &project.name, &branch);
+
+            let wt = git::add_worktree(&project.path, &branch, &path, base.as_deref())?;
+
+            println!("Worktree '{}' created", wt.name);
+            println!("  Path: {}", wt.path.display());
+        }
+        WorktreeAction::Rm {
+            name,
+            project,
+            force,
+        } => {
+            let project = resolve_bare_project(project)?;
+            let path = git::remove_worktree(&project.path, &name, force)?;
+
+            println!("Worktree '{}' removed", name);
+            println!("  Path: {}", path.display());
+        }
+        WorktreeAction::List { project } => {
+            let project = resolve_bare_project(project)?;
+            let worktrees = git::list_worktrees(&project.path)?;
+
+            if worktrees.is_empty() {
+                println!("No worktrees found");
+            }
+
+            for wt in worktrees {
+                println!("{} - {}", wt, wt.path.display());
+            }
+        }
+        WorktreeAction::Prune { project } => {
+            let project = resolve_bare_project(project)?;
+            let pruned = git::prune_worktrees(&project.path)?;
+
+            if pruned.is_empty() {
+                println!("Nothing to prune");
+            }
+
+            for name in pruned {
+                println!("Worktree '{}' pruned", name);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Resolves the bare project to operate on: the named one, the one containing
+/// the current directory, or an interactive selection.
+fn resolve_bare_project(name: Option<String>) -> Result<Project> {
+    let storage = Storage::load()?;
+
+    if let Some(name) = name {
+        let project = storage
+            .find_by_name(&name)
+            .ok_or(StorageError::ProjectNotFound(name))?;
+        if !project.is_bare_repo {
+            bail!("project '{}' is not a bare repository", project.name);
+        }
+        return Ok(project.clone());
+    }
+
+    let bare: Vec<&Project> = storage
+        .list()
+        .into_iter()
+        .filter(|p| p.is_bare_repo)
+        .collect();
+
+    let cwd = std::env::current_dir()?;
+    if let Some(project) = bare
+        .iter()
+        .filter(|p| cwd.starts_with(&p.path))
+        .max_by_key(|p| p.path.components().count())
+    {
+        return Ok((*project).clone());
+    }
+
+    if bare.is_empty() {
+        bail!("no bare repository projects registered");
+    }
+
+    Ok(Select::new("Select a project:", bare).prompt()?.clone())
+}
@@ -1,5 +1,8 @@
 use crate::config::Shell;
-use clap::{Parser, Subcommand};
+use crate::exporters::ExportFormat;
+use crate::importers::ImportSource;
+use crate::tag_query::TagQuery;
+use clap::{Args, Parser, Subcommand};
 use std::path::PathBuf;
 use strum::EnumIter;
 
@@ -12,10 +15,31 @@ pub mod commands;
     long_about = "Bivio is a fast project navigator with tags, fuzzy pickers, and first-class worktree support."
 )]
 pub struct Cli {
+    /// Profile whose registry to use (default: $BVO_PROFILE, then the
+    /// `default_profile` config key)
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+
     #[command(subcommand)]
     pub command: Command,
 }
 
+impl Cli {
+    /// Every word taken as a subcommand: names, their aliases and `help`.
+    pub fn subcommand_names() -> Vec<String> {
+        let cli = <Self as clap::CommandFactory>::command();
+        let mut names: Vec<String> = cli
+            .get_subcommands()
+            .flat_map(|c| std::iter::once(c.get_name()).chain(c.get_all_aliases()))
+            .map(String::from)
+            .collect();
+        if !names.iter().any(|n| n == "help") {
+            names.push("help".to_string());
+        }
+        names
+    }
+}
+
 #[derive(Subcommand, Clone, EnumIter, strum_macros::Display)]
 #[strum(serialize_all = "kebab-case")]
 pub enum Command {
@@ -32,16 +56,133 @@ pub enum Command {
         /// Comma-separated tags
         #[arg(short, long, value_delimiter = ',')]
         tags: Option<Vec<String>>,
+
+        /// Show the registry changes without saving them
+        #[arg(long)]
+        dry_run: bool,
     },
 
-    /// List projects (optionally filtered by tags)
-    List {
-        /// Filter by tags
+    /// Discover git repositories under a directory and register them
+    Scan {
+        /// Directory to scan
+        root: PathBuf,
+
+        /// Maximum directory depth to descend
+        #[arg(short, long, default_value_t = 3)]
+        depth: usize,
+
+        /// Comma-separated tags applied to every registered project
+        #[arg(short, long, value_delimiter = ',')]
+        tags: Option<Vec<String>>,
+
+        /// Comma-separated globs of directories to skip
+        #[arg(
+            long,
+            value_delimiter = ',',
+            default_value = "node_modules,target,vendor,.venv"
+        )]
+        ignore: Vec<String>,
+
+        /// Register every discovered repository without prompting
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Show the registry changes without saving them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Import projects from zoxide, VS Code Project Manager, projectile, ghq
+    /// or a `bvo export` dump
+    Import {
+        /// zoxide, vscode-project-manager, projectile, ghq, plain-list, or an
+        /// export format: json, toml, csv, markdown
+        #[arg(long)]
+        from: ImportSource,
+
+        /// Data file to read instead of the tool's own (`-` for stdin)
+        file: Option<PathBuf>,
+
+        /// Comma-separated tags applied to every imported project
         #[arg(short, long, value_delimiter = ',')]
         tags: Option<Vec<String>>,
 
+        /// Only import directories that are git repositories
+        #[arg(long)]
+        git_only: bool,
+
+        /// Import everything without prompting
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Show the registry changes without saving them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Export projects as json, toml, csv, markdown or a VS Code workspace
+    Export {
+        /// json, toml, csv, markdown or code-workspace
+        #[arg(short, long, default_value = "json")]
+        format: ExportFormat,
+
+        /// Filter by tag expression, e.g. 'work & rust & !archived' or 'a | b'
+        #[arg(short, long)]
+        tags: Option<TagQuery>,
+
+        /// Write to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Sync the registry through a git remote, merging changes made elsewhere
+    Sync {
+        /// Remote URL or path to sync with, saved as `sync_remote`
+        #[arg(long)]
+        remote: Option<String>,
+    },
+
+    /// Clone a repository into the configured layout and register it
+    Clone {
+        /// Remote URL, `file://` URL or local path
+        url: String,
+
+        /// Project name (default: repository name)
+        #[arg(short, long)]
+        name: Option<String>,
+
+        /// Comma-separated tags
+        #[arg(short, long, value_delimiter = ',')]
+        tags: Option<Vec<String>>,
+
+        /// Clone as a bare repository with an initial worktree
+        #[arg(long)]
+        bare: bool,
+
+        /// Clone root (default: `clone_root` config, then ~/code)
+        #[arg(long)]
+        root: Option<PathBuf>,
+
+        /// Show the registry changes without saving them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// List projects (optionally filtered by tags)
+    List {
+        #[command(flatten)]
+        selection: Selection,
+
         #[arg(long)]
         json: bool,
+
+        /// Show branch, dirty state, upstream divergence and last commit
+        #[arg(short, long)]
+        status: bool,
+
+        /// Also print the notes of each project
+        #[arg(short, long)]
+        notes: bool,
     },
 
     /// Interactive picker with fuzzy search
@@ -49,8 +190,73 @@ pub enum Command {
         /// Search project
         query: Option<String>,
 
-        #[arg(short, long, value_delimiter = ',')]
-        tags: Option<Vec<String>>,
+        #[command(flatten)]
+        selection: Selection,
+
+        /// Show a preview pane for the highlighted project
+        #[arg(short, long)]
+        preview: bool,
+
+        /// Take the best match instead of prompting
+        #[arg(long)]
+        first: bool,
+
+        /// Worktree of a bare project to jump to, by name or branch
+        #[arg(short, long)]
+        worktree: Option<String>,
+
+        /// Also match descriptions and notes
+        #[arg(short = 'n', long)]
+        search_notes: bool,
+    },
+
+    /// Show projects in ranking order with their frecency scores
+    Rank {
+        #[command(flatten)]
+        selection: Selection,
+
+        /// Show the visits, age and decay behind each score
+        #[arg(short, long)]
+        explain: bool,
+    },
+
+    /// Show the latest project visits, most recent first
+    Recent {
+        #[command(flatten)]
+        selection: Selection,
+
+        /// Number of visits to show
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Print project details, as shown in the picker preview
+    Preview {
+        /// Project name, or `profile:name`
+        name: String,
+    },
+
+    /// Print the path of a project without prompting
+    ///
+    /// Exits with 3 when nothing matches and 4 when the query is ambiguous.
+    Path {
+        /// Project name or fuzzy query
+        query: String,
+
+        /// Filter by tag expression, e.g. 'work & rust & !archived' or 'a | b'
+        #[arg(short, long)]
+        tags: Option<TagQuery>,
+
+        /// Worktree of a bare project, by name or branch
+        #[arg(short, long)]
+        worktree: Option<String>,
+
+        /// Take the best match instead of failing when the query is ambiguous
+        #[arg(long)]
+        first: bool,
     },
 
     /// Remove a project (alias: rm)
@@ -59,11 +265,52 @@ pub enum Command {
         #[arg(short, long)]
         all: bool,
 
-        #[arg(short, long, value_delimiter = ',')]
-        tags: Option<Vec<String>>,
+        /// Filter by tag expression, e.g. 'work & rust & !archived' or 'a | b'
+        #[arg(short, long)]
+        tags: Option<TagQuery>,
 
         /// Project name to remove
         name: Option<String>,
+
+        /// Show the registry changes without saving them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Rename a project, keeping its tags and history
+    Rename {
+        /// Current project name
+        name: String,
+
+        /// New project name
+        new_name: String,
+
+        /// Show the registry changes without saving them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Point a project at a new path, optionally moving it on disk
+    Mv {
+        /// Project name
+        name: String,
+
+        /// New project path
+        path: PathBuf,
+
+        /// Move the directory on disk as well
+        #[arg(short, long)]
+        move_files: bool,
+
+        /// Show the registry changes without saving them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Manage worktrees of a bare repository project
+    Wt {
+        #[command(subcommand)]
+        action: WorktreeAction,
     },
 
     /// Print shell integration hooks
@@ -83,6 +330,142 @@ pub enum Command {
         /// Remove tags instead of adding them
         #[arg(short, long)]
         remove: bool,
+
+        /// Show the registry changes without saving them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Add or remove short aliases that jump to a project directly
+    Alias {
+        /// Project name
+        project: String,
+
+        /// Aliases to add (default: print the current ones)
+        aliases: Vec<String>,
+
+        /// Remove aliases instead of adding them
+        #[arg(short, long)]
+        remove: bool,
+
+        /// Show the registry changes without saving them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Pin a project so it sorts first in `list` and `pick`
+    Pin {
+        /// Project name
+        name: String,
+
+        /// Show the registry changes without saving them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Unpin a project
+    Unpin {
+        /// Project name
+        name: String,
+
+        /// Show the registry changes without saving them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Hide a project from `list` and `pick`, keeping it in the registry
+    Archive {
+        /// Project name
+        name: String,
+
+        /// Show the registry changes without saving them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Show an archived project again
+    Unarchive {
+        /// Project name
+        name: String,
+
+        /// Show the registry changes without saving them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Show or set the one-line description of a project
+    Describe {
+        /// Project name
+        name: String,
+
+        /// New description (default: print the current one)
+        description: Option<String>,
+
+        /// Take the first paragraph of the project's README
+        #[arg(long, conflicts_with_all = ["description", "clear"])]
+        readme: bool,
+
+        /// Remove the description
+        #[arg(long, conflicts_with = "description")]
+        clear: bool,
+
+        /// Show the registry changes without saving them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Edit the notes of a project in $EDITOR
+    Note {
+        /// Project name
+        name: String,
+
+        /// Print the notes instead of editing them
+        #[arg(short, long, conflicts_with = "clear")]
+        print: bool,
+
+        /// Remove the notes
+        #[arg(long)]
+        clear: bool,
+
+        /// Show the registry changes without saving them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Revert the last registry changes
+    Undo {
+        /// Number of operations to revert
+        #[arg(
+            default_value_t = 1,
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+        )]
+        count: usize,
+
+        /// Show the registry changes without saving them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Show the log of registry changes, most recent first
+    History {
+        /// Show only the last N operations
+        #[arg(short = 'n', long)]
+        limit: Option<usize>,
+
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Find and repair broken projects, worktrees and config
+    Doctor {
+        /// Only report problems, exiting with 1 if any are found
+        #[arg(long)]
+        check: bool,
+
+        /// Show the registry changes of the fixes without saving them or
+        /// touching the config and worktrees
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// Read or update configuration
@@ -93,13 +476,34 @@ pub enum Command {
 
     /// Check for new releases
     CheckUpdate,
+
+    /// Print project names and aliases for shell completion
+    #[command(hide = true)]
+    Complete,
+}
+
+/// Which projects `list`, `pick`, `rank` and `recent` offer.
+#[derive(Args, Clone, Default)]
+pub struct Selection {
+    /// Filter by tag expression, e.g. 'work & rust & !archived' or 'a | b'
+    #[arg(short, long)]
+    pub tags: Option<TagQuery>,
+
+    /// Include the projects of every profile
+    #[arg(long, conflicts_with = "profile")]
+    pub all_profiles: bool,
+
+    /// Include archived projects
+    #[arg(short = 'a', long)]
+    pub include_archived: bool,
 }
 
 #[derive(Subcommand, Clone, Default)]
 pub enum ConfigAction {
     /// Get a configuration value
     Get {
-        /// Key to read (default_shell)
+        /// Key to read (default_shell, clone_root, clone_layout, worktree_layout, picker,
+        /// picker_format, picker_preview, picker_preview_command)
         key: String,
     },
 
@@ -115,3 +519,56 @@ pub enum ConfigAction {
     #[default]
     View,
 }
+
+#[derive(Subcommand, Clone)]
+pub enum WorktreeAction {
+    /// Create a worktree for a new or existing branch
+    Add {
+        /// Branch to check out (created if it does not exist)
+        branch: String,
+
+        /// Bare project (default: project containing the current directory)
+        #[arg(short, long)]
+        project: Option<String>,
+
+        /// Start point for a new branch (default: HEAD)
+        #[arg(short, long)]
+        base: Option<String>,
+    },
+
+    /// Remove a worktree (alias: remove)
+    #[command(alias = "remove")]
+    Rm {
+        /// Worktree name
+        name: String,
+
+        /// Bare project (default: project containing the current directory)
+        #[arg(short, long)]
+        project: Option<String>,
+
+        /// Remove even if the worktree has uncommitted changes
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// List worktrees (alias: ls)
+    #[command(alias = "ls")]
+    List {
+        /// Bare project (default: project containing the current directory)
+        #[arg(short, long)]
+        project: Option<String>,
+    },
+
+    /// Prune metadata of worktrees whose directory no longer exists
+    Prune {
+        /// Bare project (default: project containing the current directory)
+        #[arg(short, long)]
+        project: Option<String>,
+    },
+}
+
+impl Default for WorktreeAction {
+    fn default() -> Self {
+        Self::List { project: None }
+    }
+}
@@ -1,7 +1,12 @@
+use crate::git;
+use crate::profile;
+use crate::storage::PathMapper;
+use crate::utils::fs::{expand_home, write_atomic};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use strum::{Display, EnumString};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display, EnumString)]
@@ -13,9 +18,63 @@ pub enum Shell {
     Fish,
 }
 
+/// Default directory layout used by `clone`, in the style of ghq.
+pub const DEFAULT_CLONE_LAYOUT: &str = "{root}/{host}/{owner}/{repo}";
+
+/// Default candidate line given to external pickers.
+pub const DEFAULT_PICKER_FORMAT: &str = "{name}";
+
+/// Default location of worktrees created by `wt add` and `clone --bare`.
+pub const DEFAULT_WORKTREE_LAYOUT: &str = "{project}/{branch}";
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub default_shell: Option<Shell>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clone_root: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clone_layout: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worktree_layout: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub picker_preview: Option<bool>,
+    /// External picker command (e.g. `fzf`); `builtin` or unset uses inquire.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub picker: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub picker_format: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub picker_preview_command: Option<String>,
+    /// Whether the picker also matches descriptions and notes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub picker_search_notes: Option<bool>,
+    /// Whether the picker lists worktrees of bare projects as candidates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub picker_worktrees: Option<bool>,
+    /// Days after which a visit counts half as much in the ranking.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frecency_half_life_days: Option<f64>,
+    /// Total visits above which all visit counts are aged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frecency_max_visits: Option<u32>,
+    /// Whether ranking favours projects usually opened at this time of day.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frecency_context: Option<bool>,
+    /// Named roots; registry paths under one are stored as `$name/...`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub path_roots: BTreeMap<String, PathBuf>,
+    /// Prefixes of absolute registry paths rewritten on this machine.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub path_remap: BTreeMap<PathBuf, PathBuf>,
+    /// Profile used when neither `--profile` nor `BVO_PROFILE` is given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+    /// Git remote used by `bvo sync`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sync_remote: Option<String>,
+    /// Whether `bvo sync` also shares this config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sync_config: Option<bool>,
 }
 
 impl Config {
@@ -30,12 +89,8 @@ impl Config {
     }
 
     pub fn save(&self) -> Result<()> {
-        let path = Self::path();
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)?;
-        }
         let content = serde_json::to_string_pretty(self)?;
-        fs::write(path, content)?;
+        write_atomic(&Self::path(), content)?;
         Ok(())
     }
 
@@ -43,18 +98,213 @@ impl Config {
         config_dir().join("config.json")
     }
 
+    /// Root directory for `clone`, defaulting to `~/code`.
+    pub fn clone_root(&self) -> PathBuf {
+        match &self.clone_root {
+            Some(root) => expand_home(root),
+            None => dirs::home_dir()
+                .unwrap_or_else(|| PathBuf::from("~"))
+                .join("code"),
+        }
+    }
+
+    pub fn clone_layout(&self) -> &str {
+        self.clone_layout.as_deref().unwrap_or(DEFAULT_CLONE_LAYOUT)
+    }
+
+    /// Expands `worktree_layout` for `branch` of the project at `project_path`.
+    /// `{project}` is the project directory, `{name}` the project name and
+    /// `{branch}` the branch with `/` replaced by `-`.
+    pub fn worktree_path(&self, project_path: &Path, name: &str, branch: &str) -> PathBuf {
+        let layout = self
+            .worktree_layout
+            .as_deref()
+            .unwrap_or(DEFAULT_WORKTREE_LAYOUT);
+        let expanded = layout
+            .replace("{project}", &project_path.to_string_lossy())
+            .replace("{name}", name)
+            .replace("{branch}", &git::worktree_name(branch));
+
+        expand_home(Path::new(&expanded))
+    }
+
+    /// External picker command, or `None` for the built-in picker.
+    pub fn external_picker(&self) -> Option<&str> {
+        self.picker
+            .as_deref()
+            .filter(|p| !p.is_empty() && *p != "builtin")
+    }
+
+    pub fn picker_format(&self) -> &str {
+        self.picker_format
+            .as_deref()
+            .unwrap_or(DEFAULT_PICKER_FORMAT)
+    }
+
+    /// Translates registry paths for this machine, see [`PathMapper`].
+    pub fn path_mapper(&self) -> PathMapper {
+        PathMapper::new(dirs::home_dir())
+            .with_roots(
+                self.path_roots
+                    .iter()
+                    .map(|(name, root)| (name.clone(), expand_home(root))),
+            )
+            .with_remap(
+                self.path_remap
+                    .iter()
+                    .map(|(from, to)| (from.clone(), expand_home(to))),
+            )
+    }
+
+    /// Config as shared by `bvo sync`, without the settings that only make
+    /// sense on this machine.
+    pub fn shared(&self) -> Self {
+        Self {
+            path_roots: BTreeMap::new(),
+            path_remap: BTreeMap::new(),
+            sync_remote: None,
+            ..self.clone()
+        }
+    }
+
+    /// Takes the shared settings of `other`, keeping the machine specific ones.
+    pub fn adopt_shared(&mut self, other: Self) {
+        *self = Self {
+            path_roots: std::mem::take(&mut self.path_roots),
+            path_remap: std::mem::take(&mut self.path_remap),
+            sync_remote: self.sync_remote.take(),
+            ..other
+        };
+    }
+
     pub fn get(&self, key: &str) -> Option<String> {
+        if let Some(name) = key.strip_prefix("path_roots.") {
+            return self.path_roots.get(name).map(|p| p.display().to_string());
+        }
+        if let Some(from) = key.strip_prefix("path_remap.") {
+            return self
+                .path_remap
+                .get(Path::new(from))
+                .map(|p| p.display().to_string());
+        }
+
         match key {
             "default_shell" => self.default_shell.map(|s| s.to_string()),
+            "clone_root" => self.clone_root.as_ref().map(|p| p.display().to_string()),
+            "clone_layout" => self.clone_layout.clone(),
+            "worktree_layout" => self.worktree_layout.clone(),
+            "picker_preview" => self.picker_preview.map(|b| b.to_string()),
+            "picker" => self.picker.clone(),
+            "picker_format" => self.picker_format.clone(),
+            "picker_preview_command" => self.picker_preview_command.clone(),
+            "picker_search_notes" => self.picker_search_notes.map(|b| b.to_string()),
+            "picker_worktrees" => self.picker_worktrees.map(|b| b.to_string()),
+            "frecency_half_life_days" => self.frecency_half_life_days.map(|d| d.to_string()),
+            "frecency_max_visits" => self.frecency_max_visits.map(|n| n.to_string()),
+            "frecency_context" => self.frecency_context.map(|b| b.to_string()),
+            "default_profile" => self.default_profile.clone(),
+            "sync_remote" => self.sync_remote.clone(),
+            "sync_config" => self.sync_config.map(|b| b.to_string()),
             _ => None,
         }
     }
 
+    /// Sets `key` to `value`. Map entries use dotted keys, e.g.
+    /// `path_roots.work`; an empty value removes the entry.
     pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        if let Some(name) = key.strip_prefix("path_roots.") {
+            if name.is_empty() || name.contains('/') {
+                anyhow::bail!("invalid root name: {name}");
+            }
+            if value.is_empty() {
+                self.path_roots.remove(name);
+            } else {
+                self.path_roots
+                    .insert(name.to_string(), PathBuf::from(value));
+            }
+            return Ok(());
+        }
+        if let Some(from) = key.strip_prefix("path_remap.") {
+            if !Path::new(from).is_absolute() {
+                anyhow::bail!("path_remap prefixes must be absolute: {from}");
+            }
+            if value.is_empty() {
+                self.path_remap.remove(Path::new(from));
+            } else {
+                self.path_remap
+                    .insert(PathBuf::from(from), PathBuf::from(value));
+            }
+            return Ok(());
+        }
+
         match key {
             "default_shell" => {
                 self.default_shell = Some(value.parse()?);
             }
+            "clone_root" => {
+                self.clone_root = Some(PathBuf::from(value));
+            }
+            "clone_layout" => {
+                if !value.contains("{repo}") {
+                    anyhow::bail!("clone_layout must contain {{repo}}");
+                }
+                self.clone_layout = Some(value.to_string());
+            }
+            "worktree_layout" => {
+                if !value.contains("{branch}") {
+                    anyhow::bail!("worktree_layout must contain {{branch}}");
+                }
+                self.worktree_layout = Some(value.to_string());
+            }
+            "picker_preview" => {
+                self.picker_preview = Some(value.parse()?);
+            }
+            "picker" => {
+                self.picker = Some(value.to_string());
+            }
+            "picker_format" => {
+                // Names are unique, so they map a selected line back to a project.
+                if !value.contains("{name}") {
+                    anyhow::bail!("picker_format must contain {{name}}");
+                }
+                self.picker_format = Some(value.to_string());
+            }
+            "picker_preview_command" => {
+                self.picker_preview_command = Some(value.to_string());
+            }
+            "picker_search_notes" => {
+                self.picker_search_notes = Some(value.parse()?);
+            }
+            "picker_worktrees" => {
+                self.picker_worktrees = Some(value.parse()?);
+            }
+            "frecency_half_life_days" => {
+                let days: f64 = value.parse()?;
+                if !(days.is_finite() && days > 0.0) {
+                    anyhow::bail!("frecency_half_life_days must be a positive number of days");
+                }
+                self.frecency_half_life_days = Some(days);
+            }
+            "frecency_max_visits" => {
+                let visits: u32 = value.parse()?;
+                if visits == 0 {
+                    anyhow::bail!("frecency_max_visits must be at least 1");
+                }
+                self.frecency_max_visits = Some(visits);
+            }
+            "frecency_context" => {
+                self.frecency_context = Some(value.parse()?);
+            }
+            "default_profile" => {
+                profile::validate(value)?;
+                self.default_profile = Some(value.to_string());
+            }
+            "sync_remote" => {
+                self.sync_remote = Some(value.to_string());
+            }
+            "sync_config" => {
+                self.sync_config = Some(value.parse()?);
+            }
             _ => anyhow::bail!("Unknown config key: {}", key),
         }
         Ok(())
@@ -88,6 +338,7 @@ mod tests {
     fn test_config_get() {
         let config = Config {
             default_shell: Some(Shell::Fish),
+            ..Default::default()
         };
         assert_eq!(config.get("default_shell"), Some("fish".to_string()));
         assert_eq!(config.get("unknown"), None);
@@ -107,6 +358,127 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_config_clone_layout() {
+        let mut config = Config::default();
+        assert_eq!(config.clone_layout(), DEFAULT_CLONE_LAYOUT);
+
+        config.set("clone_layout", "{root}/{repo}").unwrap();
+        assert_eq!(
+            config.get("clone_layout"),
+            Some("{root}/{repo}".to_string())
+        );
+        assert!(config.set("clone_layout", "{root}/{owner}").is_err());
+    }
+
+    #[test]
+    fn test_config_worktree_path() {
+        let mut config = Config::default();
+        assert_eq!(
+            config.worktree_path(Path::new("/code/app"), "app", "feat/x"),
+            PathBuf::from("/code/app/feat-x")
+        );
+
+        config
+            .set("worktree_layout", "/wt/{name}-{branch}")
+            .unwrap();
+        assert_eq!(
+            config.worktree_path(Path::new("/code/app"), "app", "main"),
+            PathBuf::from("/wt/app-main")
+        );
+    }
+
+    #[test]
+    fn test_config_path_maps() {
+        let mut config = Config::default();
+        config.set("path_roots.work", "/srv/work").unwrap();
+        config.set("path_remap./Users/me", "/home/me").unwrap();
+        assert_eq!(config.get("path_roots.work"), Some("/srv/work".to_string()));
+        assert_eq!(
+            config.get("path_remap./Users/me"),
+            Some("/home/me".to_string())
+        );
+
+        let mapper = config.path_mapper();
+        assert_eq!(
+            mapper.to_portable(Path::new("/srv/work/api")),
+            PathBuf::from("$work/api")
+        );
+        assert_eq!(
+            mapper.to_local(Path::new("/Users/me/api")),
+            PathBuf::from("/home/me/api")
+        );
+
+        config.set("path_roots.work", "").unwrap();
+        assert_eq!(config.get("path_roots.work"), None);
+        assert!(config.set("path_remap.relative", "/x").is_err());
+    }
+
+    #[test]
+    fn test_config_default_profile() {
+        let mut config = Config::default();
+        config.set("default_profile", "work").unwrap();
+        assert_eq!(config.get("default_profile"), Some("work".to_string()));
+        assert!(config.set("default_profile", "../work").is_err());
+    }
+
+    #[test]
+    fn test_config_frecency() {
+        let mut config = Config::default();
+        config.set("frecency_half_life_days", "2.5").unwrap();
+        config.set("frecency_max_visits", "500").unwrap();
+        assert_eq!(
+            config.get("frecency_half_life_days"),
+            Some("2.5".to_string())
+        );
+        assert_eq!(config.get("frecency_max_visits"), Some("500".to_string()));
+
+        assert!(config.set("frecency_half_life_days", "0").is_err());
+        assert!(config.set("frecency_half_life_days", "inf").is_err());
+        assert!(config.set("frecency_max_visits", "0").is_err());
+
+        config.set("frecency_context", "false").unwrap();
+        assert_eq!(config.get("frecency_context"), Some("false".to_string()));
+    }
+
+    #[test]
+    fn test_config_shared() {
+        let mut config = Config::default();
+        config.set("path_roots.work", "/srv/work").unwrap();
+        config.set("sync_remote", "/tmp/remote.git").unwrap();
+        config.set("picker", "fzf").unwrap();
+
+        let shared = config.shared();
+        assert!(shared.path_roots.is_empty());
+        assert_eq!(shared.sync_remote, None);
+        assert_eq!(shared.picker.as_deref(), Some("fzf"));
+
+        let mut theirs = Config::default();
+        theirs.set("picker", "sk").unwrap();
+        theirs.set("sync_remote", "/elsewhere.git").unwrap();
+        config.adopt_shared(theirs);
+        assert_eq!(config.picker.as_deref(), Some("sk"));
+        assert_eq!(config.sync_remote.as_deref(), Some("/tmp/remote.git"));
+        assert_eq!(config.get("path_roots.work"), Some("/srv/work".to_string()));
+    }
+
+    #[test]
+    fn test_config_external_picker() {
+        let mut config = Config::default();
+        assert_eq!(config.external_picker(), None);
+
+        config.set("picker", "builtin").unwrap();
+        assert_eq!(config.external_picker(), None);
+
+        config.set("picker", "fzf --height 40%").unwrap();
+        assert_eq!(config.external_picker(), Some("fzf --height 40%"));
+
+        assert!(config.set("picker_format", "{path}").is_err());
+
+        config.set("picker_worktrees", "false").unwrap();
+        assert_eq!(config.get("picker_worktrees"), Some("false".to_string()));
+    }
+
     #[test]
     fn test_shell_display() {
         assert_eq!(Shell::Zsh.to_string(), "zsh");
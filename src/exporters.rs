@@ -0,0 +1,344 @@
+use crate::Project;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::PathBuf;
+use strum::{Display, EnumString};
+
+/// Formats written by `bvo export`. All but `code-workspace` can be read back
+/// with `bvo import --from <format>`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "kebab-case")]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Toml,
+    Csv,
+    Markdown,
+    /// VS Code multi-root workspace
+    CodeWorkspace,
+}
+
+/// Portable form of a project: what another machine needs to recreate it.
+/// Bare state is left out since it is re-detected on import.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedProject {
+    pub name: String,
+    pub path: PathBuf,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub visits: u32,
+    pub added_at: Option<DateTime<Utc>>,
+    pub last_opened_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub archived: bool,
+}
+
+impl From<&Project> for ExportedProject {
+    fn from(project: &Project) -> Self {
+        Self {
+            name: project.name.clone(),
+            path: project.path.clone(),
+            tags: project.tags.clone(),
+            visits: project.visits,
+            added_at: Some(project.added_at),
+            last_opened_at: Some(project.last_opened_at),
+            aliases: project.aliases.clone(),
+            description: project.description.clone(),
+            notes: project.notes.clone(),
+            pinned: project.pinned,
+            archived: project.archived,
+        }
+    }
+}
+
+/// TOML documents must be tables, so projects go under `[[projects]]`.
+#[derive(Serialize, Deserialize)]
+struct TomlDocument {
+    projects: Vec<ExportedProject>,
+}
+
+/// CSV has no lists, so tags and aliases are joined with `,` in a single
+/// column. Columns after `last_opened_at` may be missing in older exports.
+#[derive(Serialize, Deserialize)]
+struct CsvRecord {
+    name: String,
+    path: PathBuf,
+    tags: String,
+    visits: u32,
+    added_at: Option<DateTime<Utc>>,
+    last_opened_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    aliases: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    notes: Option<String>,
+    #[serde(default)]
+    pinned: bool,
+    #[serde(default)]
+    archived: bool,
+}
+
+/// Columns after "Last opened" may be missing in older exports.
+const MARKDOWN_HEADER: [&str; 11] = [
+    "Name",
+    "Path",
+    "Tags",
+    "Visits",
+    "Added",
+    "Last opened",
+    "Aliases",
+    "Pinned",
+    "Archived",
+    "Description",
+    "Notes",
+];
+
+pub fn render(format: ExportFormat, projects: &[&Project]) -> Result<String> {
+    let exported: Vec<ExportedProject> = projects.iter().map(|p| (*p).into()).collect();
+
+    Ok(match format {
+        ExportFormat::Json => serde_json::to_string_pretty(&exported)?,
+        ExportFormat::Toml => toml::to_string(&TomlDocument { projects: exported })?,
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            for p in exported {
+                writer.serialize(CsvRecord {
+                    tags: p.tags.join(","),
+                    name: p.name,
+                    path: p.path,
+                    visits: p.visits,
+                    added_at: p.added_at,
+                    last_opened_at: p.last_opened_at,
+                    aliases: p.aliases.join(","),
+                    description: p.description,
+                    notes: p.notes,
+                    pinned: p.pinned,
+                    archived: p.archived,
+                })?;
+            }
+            String::from_utf8(writer.into_inner()?)?
+        }
+        ExportFormat::Markdown => render_markdown(&exported),
+        ExportFormat::CodeWorkspace => {
+            let folders: Vec<_> = exported
+                .iter()
+                .map(|p| json!({ "name": p.name, "path": p.path }))
+                .collect();
+            serde_json::to_string_pretty(&json!({ "folders": folders, "settings": {} }))?
+        }
+    })
+}
+
+/// Reads back what [`render`] wrote.
+pub fn parse(format: ExportFormat, input: &str) -> Result<Vec<ExportedProject>> {
+    Ok(match format {
+        ExportFormat::Json => serde_json::from_str(input)?,
+        ExportFormat::Toml => toml::from_str::<TomlDocument>(input)?.projects,
+        ExportFormat::Csv => csv::Reader::from_reader(input.as_bytes())
+            .deserialize::<CsvRecord>()
+            .map(|record| {
+                let r = record?;
+                Ok(ExportedProject {
+                    name: r.name,
+                    path: r.path,
+                    tags: split_tags(&r.tags),
+                    visits: r.visits,
+                    added_at: r.added_at,
+                    last_opened_at: r.last_opened_at,
+                    aliases: split_tags(&r.aliases),
+                    description: r.description,
+                    notes: r.notes,
+                    pinned: r.pinned,
+                    archived: r.archived,
+                })
+            })
+            .collect::<Result<_>>()?,
+        ExportFormat::Markdown => parse_markdown(input)?,
+        ExportFormat::CodeWorkspace => bail!("code-workspace files cannot be imported"),
+    })
+}
+
+fn split_tags(tags: &str) -> Vec<String> {
+    tags.split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn render_markdown(projects: &[ExportedProject]) -> String {
+    let row = |cells: &[String]| format!("| {} |\n", cells.join(" | "));
+    let timestamp = |t: Option<DateTime<Utc>>| t.map(|t| t.to_rfc3339()).unwrap_or_default();
+    let flag = |set: bool| if set { "yes" } else { "" }.to_string();
+
+    let mut out = row(&MARKDOWN_HEADER.map(String::from));
+    out.push_str(&row(&MARKDOWN_HEADER.map(|_| "---".to_string())));
+    for p in projects {
+        out.push_str(&row(&[
+            escape_cell(&p.name),
+            escape_cell(&p.path.to_string_lossy()),
+            escape_cell(&p.tags.join(", ")),
+            p.visits.to_string(),
+            timestamp(p.added_at),
+            timestamp(p.last_opened_at),
+            escape_cell(&p.aliases.join(", ")),
+            flag(p.pinned),
+            flag(p.archived),
+            escape_cell(p.description.as_deref().unwrap_or_default()),
+            escape_cell(p.notes.as_deref().unwrap_or_default()),
+        ]));
+    }
+
+    out
+}
+
+fn parse_markdown(input: &str) -> Result<Vec<ExportedProject>> {
+    let timestamp = |cell: &str| -> Result<Option<DateTime<Utc>>> {
+        if cell.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(
+            DateTime::parse_from_rfc3339(cell)?.with_timezone(&Utc),
+        ))
+    };
+
+    input
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with('|'))
+        .map(split_row)
+        // Header and `---` separator
+        .filter(|cells| {
+            cells
+                .first()
+                .is_some_and(|c| c != MARKDOWN_HEADER[0] && !c.starts_with("---"))
+        })
+        .map(|cells| {
+            let [name, path, tags, visits, added_at, last_opened_at, rest @ ..] = cells.as_slice()
+            else {
+                bail!(
+                    "expected {} columns, got {}",
+                    MARKDOWN_HEADER.len(),
+                    cells.len()
+                );
+            };
+            let cell = |i: usize| rest.get(i).map(String::as_str).unwrap_or_default();
+            let text = |i: usize| Some(cell(i).to_string()).filter(|t| !t.is_empty());
+            Ok(ExportedProject {
+                name: name.clone(),
+                path: PathBuf::from(path),
+                tags: split_tags(tags),
+                visits: visits.parse().unwrap_or(0),
+                added_at: timestamp(added_at)?,
+                last_opened_at: timestamp(last_opened_at)?,
+                aliases: split_tags(cell(0)),
+                pinned: cell(1) == "yes",
+                archived: cell(2) == "yes",
+                description: text(3),
+                notes: text(4),
+            })
+        })
+        .collect()
+}
+
+/// Escapes `\`, `|` and newlines, which would end the cell or the row.
+fn escape_cell(cell: &str) -> String {
+    cell.replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace('\n', "\\n")
+}
+
+/// Splits `| a | b\|c |` into unescaped, trimmed cells.
+fn split_row(line: &str) -> Vec<String> {
+    let inner = line.trim().trim_start_matches('|');
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = inner.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => cell.push('\n'),
+                escaped => cell.extend(escaped),
+            },
+            '|' => cells.push(std::mem::take(&mut cell).trim().to_string()),
+            c => cell.push(c),
+        }
+    }
+    if !cell.trim().is_empty() {
+        cells.push(cell.trim().to_string());
+    }
+
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn projects() -> Vec<Project> {
+        let mut api = Project::new("api".to_string(), PathBuf::from("/srv/api"), false)
+            .with_tags(vec!["work".to_string(), "rust".to_string()]);
+        api.visits = 12;
+        api.aliases = vec!["a".to_string(), "srv".to_string()];
+        api.description = Some("Public API".to_string());
+        api.notes = Some("Deploy with `make ship`.\nOn-call: #api | ops\\n".to_string());
+        api.pinned = true;
+        let mut odd = Project::new("odd|name".to_string(), PathBuf::from("/tmp/a,b"), false);
+        odd.archived = true;
+        vec![api, odd]
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let projects = projects();
+        let refs: Vec<&Project> = projects.iter().collect();
+        let expected: Vec<ExportedProject> = refs.iter().map(|p| (*p).into()).collect();
+
+        for format in [
+            ExportFormat::Json,
+            ExportFormat::Toml,
+            ExportFormat::Csv,
+            ExportFormat::Markdown,
+        ] {
+            let rendered = render(format, &refs).unwrap();
+            assert_eq!(parse(format, &rendered).unwrap(), expected, "{format}");
+        }
+    }
+
+    #[test]
+    fn test_parse_older_markdown() {
+        let input = indoc::indoc! {"
+            | Name | Path | Tags | Visits | Added | Last opened |
+            | --- | --- | --- | --- | --- | --- |
+            | api | /srv/api | work | 3 |  |  |
+        "};
+        let parsed = parse(ExportFormat::Markdown, input).unwrap();
+        assert_eq!(parsed[0].visits, 3);
+        assert!(parsed[0].aliases.is_empty());
+        assert_eq!(parsed[0].notes, None);
+    }
+
+    #[test]
+    fn test_code_workspace() {
+        let projects = projects();
+        let rendered = render(ExportFormat::CodeWorkspace, &[&projects[0]]).unwrap();
+        let workspace: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+
+        assert_eq!(workspace["folders"][0]["name"], "api");
+        assert_eq!(workspace["folders"][0]["path"], "/srv/api");
+        assert!(parse(ExportFormat::CodeWorkspace, &rendered).is_err());
+    }
+}
@@ -1,6 +1,14 @@
-use anyhow::Result;
-use git2::Repository;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use git2::{
+    build::RepoBuilder, BranchType, Reference, Repository, StatusOptions, WorktreeAddOptions,
+    WorktreeLockStatus, WorktreePruneOptions,
+};
+use ignore::{overrides::OverrideBuilder, WalkBuilder, WalkState};
+use serde::Serialize;
+use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 #[allow(dead_code)]
 pub fn get_repo_root(path: &Path) -> Result<PathBuf> {
@@ -14,18 +22,115 @@ pub fn get_repo_root(path: &Path) -> Result<PathBuf> {
     }
 }
 
+pub fn is_repo(path: &Path) -> bool {
+    Repository::open(path).is_ok()
+}
+
 pub fn is_bare_repo(path: &Path) -> bool {
     Repository::open(path)
         .map(|repo| repo.is_bare())
         .unwrap_or(false)
 }
 
+/// A repository found by [`discover_repositories`].
+#[derive(Debug, Clone)]
+pub struct DiscoveredRepo {
+    pub path: PathBuf,
+    pub is_bare: bool,
+}
+
+/// Walks `root` in parallel looking for git repositories, skipping any
+/// directory matching one of the `ignore` globs.
+///
+/// The walk does not descend into a repository once found. Linked worktrees
+/// and submodules (a `.git` file pointing elsewhere) are not reported, since
+/// they are reached through the repository that owns them.
+pub fn discover_repositories(
+    root: &Path,
+    max_depth: Option<usize>,
+    ignore: &[String],
+) -> Result<Vec<DiscoveredRepo>> {
+    let mut overrides = OverrideBuilder::new(root);
+    for glob in ignore {
+        overrides.add(&format!("!{glob}"))?;
+    }
+
+    let found = Mutex::new(Vec::new());
+
+    WalkBuilder::new(root)
+        .standard_filters(false)
+        .max_depth(max_depth)
+        .overrides(overrides.build()?)
+        .build_parallel()
+        .run(|| {
+            Box::new(|entry| {
+                let Ok(entry) = entry else {
+                    return WalkState::Continue;
+                };
+                if !entry.file_type().is_some_and(|t| t.is_dir()) {
+                    return WalkState::Continue;
+                }
+
+                match detect_repo(entry.path()) {
+                    Some(repo) => {
+                        found.lock().unwrap().push(repo);
+                        WalkState::Skip
+                    }
+                    None => WalkState::Continue,
+                }
+            })
+        });
+
+    let mut found = found.into_inner().unwrap();
+    found.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(found)
+}
+
+impl std::fmt::Display for DiscoveredRepo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_bare {
+            return write!(f, "{} (bare)", self.path.display());
+        }
+
+        write!(f, "{}", self.path.display())
+    }
+}
+
+fn detect_repo(dir: &Path) -> Option<DiscoveredRepo> {
+    let dot_git = dir.join(".git");
+
+    if dot_git.is_dir() {
+        return Some(DiscoveredRepo {
+            path: dir.to_path_buf(),
+            is_bare: false,
+        });
+    }
+
+    // Either a bare repository itself, or a `.git` file pointing to one
+    // (the `.bare` + worktrees layout). Cheap checks first, `git2` last.
+    let looks_bare = dir.join("HEAD").is_file() && dir.join("objects").is_dir();
+    if (looks_bare || dot_git.is_file()) && is_bare_repo(dir) {
+        return Some(DiscoveredRepo {
+            path: dir.to_path_buf(),
+            is_bare: true,
+        });
+    }
+
+    None
+}
+
 pub fn list_worktrees(bare_repo_path: &Path) -> Result<Vec<Worktree>> {
     let repo = Repository::open(bare_repo_path)?;
     let mut worktrees = Vec::new();
 
     for name in repo.worktrees()?.iter().flatten() {
-        if let Ok(wt) = repo.find_worktree(name) {
+        // Worktrees whose directory is gone are skipped; see `prune_worktrees`.
+        if let Some(wt) = repo
+            .find_worktree(name)
+            .ok()
+            .filter(|wt| wt.validate().is_ok())
+        {
             let wtrepo = Repository::open(wt.path())?;
 
             worktrees.push(Worktree {
@@ -39,6 +144,300 @@ pub fn list_worktrees(bare_repo_path: &Path) -> Result<Vec<Worktree>> {
     Ok(worktrees)
 }
 
+/// Clones `url` (a remote URL, `file://` URL or local path) into `dest`.
+pub fn clone_repo(url: &str, dest: &Path) -> Result<()> {
+    RepoBuilder::new().clone(url, dest)?;
+    Ok(())
+}
+
+/// Clones `url` as a bare repository into `dest/.bare` and points `dest/.git`
+/// at it. Returns the default branch, ready to be checked out with
+/// [`add_worktree`].
+pub fn clone_bare(url: &str, dest: &Path) -> Result<String> {
+    let repo = RepoBuilder::new()
+        .bare(true)
+        .clone(url, &dest.join(".bare"))?;
+    fs::write(dest.join(".git"), "gitdir: ./.bare\n")?;
+
+    let branch = repo
+        .head()?
+        .shorthand()
+        .map(|s| s.to_string())
+        .ok_or_else(|| anyhow::anyhow!("remote HEAD is not a branch"))?;
+
+    Ok(branch)
+}
+
+/// Creates a worktree at `path` for `branch`.
+///
+/// An existing local branch is checked out as is. Otherwise the branch is
+/// created from a remote branch of the same name (and set to track it), or
+/// from `base` (default: `HEAD`) when no remote has it.
+pub fn add_worktree(
+    repo_path: &Path,
+    branch: &str,
+    path: &Path,
+    base: Option<&str>,
+) -> Result<Worktree> {
+    let repo = Repository::open(repo_path)?;
+
+    // `feat/login` and `feat-login` share a name; never reuse the other's.
+    let name = worktree_name(branch);
+    if let Ok(existing) = repo.find_worktree(&name) {
+        let other = Repository::open_from_worktree(&existing)
+            .ok()
+            .and_then(|r| r.head().ok()?.shorthand().map(String::from));
+        match other {
+            Some(other) => bail!("worktree '{name}' already exists for branch '{other}'"),
+            None => bail!("worktree '{name}' already exists"),
+        }
+    }
+
+    // A true bare repository is its own git directory, so the default layout
+    // puts worktrees next to `objects`, `refs` and the like.
+    let git_dir = fs::canonicalize(repo.path())?;
+    if let Ok(inside) = resolve_path(path).strip_prefix(&git_dir) {
+        let clashes = inside.components().next().is_none_or(|first| {
+            GIT_DIR_ENTRIES.contains(&first.as_os_str().to_string_lossy().as_ref())
+        });
+        if clashes {
+            bail!(
+                "worktree path '{}' clashes with the git directory '{}'",
+                path.display(),
+                git_dir.display()
+            );
+        }
+    }
+
+    let reference = find_or_create_branch(&repo, branch, base)?;
+    let mut opts = WorktreeAddOptions::new();
+    opts.reference(Some(&reference));
+    let wt = repo.worktree(&name, path, Some(&opts))?;
+
+    Ok(Worktree {
+        name,
+        path: wt.path().to_path_buf(),
+        branch: reference.name().map(|s| s.to_string()),
+    })
+}
+
+/// Entries git keeps in a repository's git directory.
+const GIT_DIR_ENTRIES: [&str; 17] = [
+    "HEAD",
+    "FETCH_HEAD",
+    "ORIG_HEAD",
+    "branches",
+    "config",
+    "description",
+    "hooks",
+    "index",
+    "info",
+    "lfs",
+    "logs",
+    "modules",
+    "objects",
+    "packed-refs",
+    "refs",
+    "shallow",
+    "worktrees",
+];
+
+/// `path` made absolute with symlinks resolved, even when it does not exist
+/// yet: the longest existing ancestor is canonicalized and the rest appended.
+fn resolve_path(path: &Path) -> PathBuf {
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    for ancestor in path.ancestors() {
+        if let Ok(resolved) = fs::canonicalize(ancestor) {
+            let rest = path.strip_prefix(ancestor).unwrap_or(Path::new(""));
+            return resolved.join(rest);
+        }
+    }
+    path
+}
+
+fn find_or_create_branch<'r>(
+    repo: &'r Repository,
+    branch: &str,
+    base: Option<&str>,
+) -> Result<Reference<'r>> {
+    if let Ok(local) = repo.find_branch(branch, BranchType::Local) {
+        return Ok(local.into_reference());
+    }
+
+    for remote in repo.remotes()?.iter().flatten() {
+        let upstream = format!("{remote}/{branch}");
+        if let Ok(tracking) = repo.find_branch(&upstream, BranchType::Remote) {
+            let commit = tracking.get().peel_to_commit()?;
+            let mut local = repo.branch(branch, &commit, false)?;
+            local.set_upstream(Some(&upstream))?;
+            return Ok(local.into_reference());
+        }
+    }
+
+    let commit = repo
+        .revparse_single(base.unwrap_or("HEAD"))?
+        .peel_to_commit()?;
+
+    Ok(repo.branch(branch, &commit, false)?.into_reference())
+}
+
+/// Returns `true` when the working tree at `path` has uncommitted changes,
+/// including untracked files.
+pub fn is_dirty(path: &Path) -> Result<bool> {
+    let repo = Repository::open(path)?;
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(true).include_ignored(false);
+
+    let dirty = !repo.statuses(Some(&mut opts))?.is_empty();
+    Ok(dirty)
+}
+
+/// Working tree state shown by `list --status`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RepoStatus {
+    /// Checked out branch, `None` for a detached or unborn `HEAD`.
+    pub branch: Option<String>,
+    pub dirty: bool,
+    pub upstream: Option<String>,
+    pub ahead: usize,
+    pub behind: usize,
+    pub last_commit_at: Option<DateTime<Utc>>,
+}
+
+impl std::fmt::Display for RepoStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let branch = self.branch.as_deref().unwrap_or("(detached)");
+        let state = if self.dirty { "dirty" } else { "clean" };
+        write!(f, "{branch} {state}")?;
+
+        if self.upstream.is_some() {
+            write!(f, " ↑{} ↓{}", self.ahead, self.behind)?;
+        }
+
+        if let Some(date) = self.last_commit_at {
+            write!(f, " · last commit {}", date.format("%Y-%m-%d %H:%M"))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads branch, dirty state, upstream divergence and last commit date of the
+/// working tree at `path`.
+pub fn status(path: &Path) -> Result<RepoStatus> {
+    let repo = Repository::open(path)?;
+    let mut status = RepoStatus {
+        dirty: is_dirty(path)?,
+        ..Default::default()
+    };
+
+    let Ok(head) = repo.head() else {
+        return Ok(status);
+    };
+
+    if let Ok(commit) = head.peel_to_commit() {
+        status.last_commit_at = DateTime::from_timestamp(commit.time().seconds(), 0);
+    }
+
+    if !head.is_branch() {
+        return Ok(status);
+    }
+    status.branch = head.shorthand().map(|s| s.to_string());
+
+    let upstream = status
+        .branch
+        .as_deref()
+        .and_then(|b| repo.find_branch(b, BranchType::Local).ok())
+        .and_then(|b| b.upstream().ok());
+
+    if let Some(upstream) = upstream {
+        if let (Some(local), Some(remote)) = (head.target(), upstream.get().target()) {
+            (status.ahead, status.behind) = repo.graph_ahead_behind(local, remote)?;
+        }
+        status.upstream = upstream.name().ok().flatten().map(|s| s.to_string());
+    }
+
+    Ok(status)
+}
+
+/// Deletes the worktree directory and its metadata. Refuses to touch a
+/// locked worktree or one with uncommitted changes unless `force` is set.
+/// Everything is checked before any file is deleted.
+pub fn remove_worktree(repo_path: &Path, name: &str, force: bool) -> Result<PathBuf> {
+    let repo = Repository::open(repo_path)?;
+    let wt = repo.find_worktree(name)?;
+    let path = wt.path().to_path_buf();
+
+    if !force {
+        if let WorktreeLockStatus::Locked(reason) = wt.is_locked()? {
+            let reason = reason.map(|r| format!(" ({r})")).unwrap_or_default();
+            bail!("worktree '{name}' is locked{reason} (use --force to remove it anyway)");
+        }
+        if path.exists() && is_dirty(&path)? {
+            bail!("worktree '{name}' has uncommitted changes (use --force to remove it anyway)");
+        }
+    }
+
+    let prune_options = || {
+        let mut opts = WorktreePruneOptions::new();
+        opts.valid(true).locked(force).working_tree(true);
+        opts
+    };
+    if !wt.is_prunable(Some(&mut prune_options()))? {
+        bail!("worktree '{name}' cannot be removed");
+    }
+
+    if path.exists() {
+        fs::remove_dir_all(&path)?;
+    }
+    wt.prune(Some(&mut prune_options()))?;
+
+    Ok(path)
+}
+
+/// Names of worktrees whose directory no longer exists.
+pub fn stale_worktrees(repo_path: &Path) -> Result<Vec<String>> {
+    let repo = Repository::open(repo_path)?;
+    let mut stale = Vec::new();
+
+    for name in repo.worktrees()?.iter().flatten() {
+        if repo.find_worktree(name)?.is_prunable(None)? {
+            stale.push(name.to_string());
+        }
+    }
+
+    Ok(stale)
+}
+
+/// URL of the `origin` remote, if the repository has one.
+pub fn origin_url(path: &Path) -> Option<String> {
+    let repo = Repository::open(path).ok()?;
+    let remote = repo.find_remote("origin").ok()?;
+    remote.url().map(|url| url.to_string())
+}
+
+/// Removes metadata of worktrees whose directory no longer exists, returning
+/// their names.
+pub fn prune_worktrees(repo_path: &Path) -> Result<Vec<String>> {
+    let repo = Repository::open(repo_path)?;
+    let mut pruned = Vec::new();
+
+    for name in repo.worktrees()?.iter().flatten() {
+        let wt = repo.find_worktree(name)?;
+        if wt.is_prunable(None)? {
+            wt.prune(None)?;
+            pruned.push(name.to_string());
+        }
+    }
+
+    Ok(pruned)
+}
+
+/// Worktree names cannot contain `/`, so `feat/login` becomes `feat-login`.
+pub fn worktree_name(branch: &str) -> String {
+    branch.replace('/', "-")
+}
+
 #[derive(Debug, Clone)]
 pub struct Worktree {
     pub name: String,
@@ -46,12 +445,245 @@ pub struct Worktree {
     pub branch: Option<String>,
 }
 
+impl Worktree {
+    /// Branch without the `refs/heads/` prefix.
+    pub fn short_branch(&self) -> Option<&str> {
+        self.branch
+            .as_deref()
+            .map(|b| b.strip_prefix("refs/heads/").unwrap_or(b))
+    }
+}
+
 impl std::fmt::Display for Worktree {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if let Some(branch) = &self.branch {
-            return write!(f, "{} [{}]", self.name, branch.replace("refs/heads/", ""));
+        if let Some(branch) = self.short_branch() {
+            return write!(f, "{} [{}]", self.name, branch);
         }
 
         write!(f, "{}", self.name)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_discover_repositories() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+
+        Repository::init(root.join("code/alpha")).unwrap();
+        Repository::init_bare(root.join("code/beta.git")).unwrap();
+        Repository::init(root.join("code/node_modules/dep")).unwrap();
+        Repository::init(root.join("code/alpha/nested")).unwrap();
+        fs::create_dir_all(root.join("code/plain")).unwrap();
+
+        let found = discover_repositories(root, None, &["node_modules".to_string()]).unwrap();
+        let found: Vec<_> = found
+            .iter()
+            .map(|r| (r.path.strip_prefix(root).unwrap().to_path_buf(), r.is_bare))
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                (PathBuf::from("code/alpha"), false),
+                (PathBuf::from("code/beta.git"), true),
+            ]
+        );
+    }
+
+    /// Creates a non-bare repository at `path` with a single commit on `main`.
+    pub(crate) fn init_with_commit(path: &Path) -> Repository {
+        let mut opts = git2::RepositoryInitOptions::new();
+        opts.initial_head("main");
+        let repo = Repository::init_opts(path, &opts).unwrap();
+        {
+            let sig = git2::Signature::now("test", "test@example.com").unwrap();
+            let tree_id = repo.index().unwrap().write_tree().unwrap();
+            let tree = repo.find_tree(tree_id).unwrap();
+            repo.commit(Some("HEAD"), &sig, &sig, "initial", &tree, &[])
+                .unwrap();
+        }
+        repo
+    }
+
+    #[test]
+    fn test_clone_bare_with_worktree() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("source");
+        init_with_commit(&source);
+
+        let dest = dir.path().join("dest");
+        let url = format!("file://{}", source.display());
+        let branch = clone_bare(&url, &dest).unwrap();
+        let wt = add_worktree(&dest, &branch, &dest.join(&branch), None).unwrap();
+
+        assert!(is_bare_repo(&dest));
+        assert_eq!(wt.path, dest.join("main"));
+        assert!(dest.join("main/.git").is_file());
+
+        let worktrees = list_worktrees(&dest).unwrap();
+        assert_eq!(worktrees.len(), 1);
+        assert_eq!(worktrees[0].branch.as_deref(), Some("refs/heads/main"));
+    }
+
+    fn bare_clone(dir: &Path) -> PathBuf {
+        let source = dir.join("source");
+        init_with_commit(&source);
+        let dest = dir.join("dest");
+        clone_bare(source.to_str().unwrap(), &dest).unwrap();
+        dest
+    }
+
+    #[test]
+    fn test_add_worktree_new_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = bare_clone(dir.path());
+
+        let wt = add_worktree(&repo, "feat/login", &repo.join("feat-login"), None).unwrap();
+        assert_eq!(wt.name, "feat-login");
+        assert_eq!(wt.branch.as_deref(), Some("refs/heads/feat/login"));
+        assert!(!is_dirty(&wt.path).unwrap());
+    }
+
+    #[test]
+    fn test_remove_worktree_refuses_dirty() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = bare_clone(dir.path());
+        let wt = add_worktree(&repo, "topic", &repo.join("topic"), None).unwrap();
+
+        fs::write(wt.path.join("scratch.txt"), "wip").unwrap();
+        assert!(is_dirty(&wt.path).unwrap());
+        assert!(remove_worktree(&repo, "topic", false).is_err());
+        assert!(wt.path.exists());
+
+        remove_worktree(&repo, "topic", true).unwrap();
+        assert!(!wt.path.exists());
+        assert!(list_worktrees(&repo).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_remove_worktree_refuses_locked() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = bare_clone(dir.path());
+        let wt = add_worktree(&repo, "topic", &repo.join("topic"), None).unwrap();
+        Repository::open(&repo)
+            .unwrap()
+            .find_worktree("topic")
+            .unwrap()
+            .lock(Some("on a USB drive"))
+            .unwrap();
+
+        let err = remove_worktree(&repo, "topic", false).unwrap_err();
+        assert!(err.to_string().contains("locked"));
+        assert!(wt.path.exists());
+        assert_eq!(list_worktrees(&repo).unwrap().len(), 1);
+
+        remove_worktree(&repo, "topic", true).unwrap();
+        assert!(!wt.path.exists());
+        assert!(list_worktrees(&repo).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_add_worktree_rejects_name_collision() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = bare_clone(dir.path());
+        add_worktree(&repo, "feat/login", &repo.join("a"), None).unwrap();
+
+        let err = add_worktree(&repo, "feat-login", &repo.join("b"), None).unwrap_err();
+        assert!(err.to_string().contains("feat/login"));
+        assert!(!repo.join("b").exists());
+        let repository = Repository::open(&repo).unwrap();
+        assert!(repository
+            .find_branch("feat-login", BranchType::Local)
+            .is_err());
+    }
+
+    #[test]
+    fn test_add_worktree_rejects_git_dir_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let bare = dir.path().join("repo.git");
+        Repository::init_bare(&bare).unwrap();
+
+        for branch in ["hooks", "refs", "objects"] {
+            let err = add_worktree(&bare, branch, &bare.join(branch), None).unwrap_err();
+            assert!(err.to_string().contains("clashes"), "{branch}: {err}");
+        }
+        let err = add_worktree(&bare, "main", &bare, None).unwrap_err();
+        assert!(err.to_string().contains("clashes"));
+        assert!(bare.join("hooks").is_dir());
+    }
+
+    #[test]
+    fn test_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("source");
+        init_with_commit(&source);
+
+        let clone = dir.path().join("clone");
+        clone_repo(source.to_str().unwrap(), &clone).unwrap();
+
+        let status = super::status(&clone).unwrap();
+        assert_eq!(status.branch.as_deref(), Some("main"));
+        assert_eq!(status.upstream.as_deref(), Some("origin/main"));
+        assert_eq!((status.ahead, status.behind), (0, 0));
+        assert!(!status.dirty);
+        assert!(status.last_commit_at.is_some());
+
+        let repo = Repository::open(&clone).unwrap();
+        let sig = git2::Signature::now("test", "test@example.com").unwrap();
+        let parent = repo.head().unwrap().peel_to_commit().unwrap();
+        let tree = parent.tree().unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "local", &tree, &[&parent])
+            .unwrap();
+        fs::write(clone.join("wip.txt"), "wip").unwrap();
+
+        let status = super::status(&clone).unwrap();
+        assert_eq!((status.ahead, status.behind), (1, 0));
+        assert!(status.dirty);
+    }
+
+    #[test]
+    fn test_prune_worktrees() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = bare_clone(dir.path());
+        let wt = add_worktree(&repo, "stale", &repo.join("stale"), None).unwrap();
+
+        fs::remove_dir_all(&wt.path).unwrap();
+        assert!(list_worktrees(&repo).unwrap().is_empty());
+        assert_eq!(stale_worktrees(&repo).unwrap(), vec!["stale".to_string()]);
+        assert_eq!(prune_worktrees(&repo).unwrap(), vec!["stale".to_string()]);
+        assert!(prune_worktrees(&repo).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_origin_url() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("source");
+        init_with_commit(&source);
+        let clone = dir.path().join("clone");
+        clone_repo(source.to_str().unwrap(), &clone).unwrap();
+
+        assert_eq!(origin_url(&clone).as_deref(), source.to_str());
+        assert_eq!(origin_url(&source), None);
+    }
+
+    #[test]
+    fn test_discover_repositories_respects_depth() {
+        let dir = tempfile::tempdir().unwrap();
+        Repository::init(dir.path().join("a/b/c")).unwrap();
+
+        assert!(discover_repositories(dir.path(), Some(2), &[])
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            discover_repositories(dir.path(), Some(3), &[])
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,346 @@
+use crate::exporters::{self, ExportFormat};
+use crate::utils::fs::expand_home;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use strum::{Display, EnumString};
+
+/// Tools whose project lists `bvo import` understands.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "kebab-case")]
+pub enum ImportSource {
+    /// `zoxide query --list --score`
+    #[default]
+    Zoxide,
+    /// `projects.json` of the VS Code Project Manager extension
+    VscodeProjectManager,
+    /// Emacs `projectile-bookmarks.eld`
+    Projectile,
+    /// `ghq list --full-path`
+    Ghq,
+    /// One path per line, `#` starts a comment
+    PlainList,
+    /// `bvo export --format json`
+    Json,
+    /// `bvo export --format toml`
+    Toml,
+    /// `bvo export --format csv`
+    Csv,
+    /// `bvo export --format markdown`
+    Markdown,
+}
+
+/// A project as found in another tool's data. Fields the tool does not track
+/// are left empty.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedProject {
+    pub name: Option<String>,
+    pub path: PathBuf,
+    pub tags: Vec<String>,
+    pub visits: Option<u32>,
+    pub added_at: Option<DateTime<Utc>>,
+    pub last_opened_at: Option<DateTime<Utc>>,
+    pub aliases: Vec<String>,
+    pub description: Option<String>,
+    pub notes: Option<String>,
+    pub pinned: bool,
+    pub archived: bool,
+}
+
+impl ImportedProject {
+    fn at(path: &str) -> Self {
+        Self {
+            name: None,
+            path: expand_home(Path::new(path.trim_end_matches('/'))),
+            tags: Vec::new(),
+            visits: None,
+            added_at: None,
+            last_opened_at: None,
+            aliases: Vec::new(),
+            description: None,
+            notes: None,
+            pinned: false,
+            archived: false,
+        }
+    }
+}
+
+impl ImportSource {
+    /// Reads `file` (`-` for stdin), or the tool's own data file or command
+    /// output when no file is given.
+    pub fn read(self, file: Option<&Path>) -> Result<String> {
+        match file {
+            Some(path) if path == Path::new("-") => {
+                let mut input = String::new();
+                io::stdin().read_to_string(&mut input)?;
+                Ok(input)
+            }
+            Some(path) => fs::read_to_string(path)
+                .with_context(|| format!("failed to read '{}'", path.display())),
+            None => match self {
+                Self::Zoxide => run("zoxide", &["query", "--list", "--score"]),
+                Self::Ghq => run("ghq", &["list", "--full-path"]),
+                Self::VscodeProjectManager | Self::Projectile => {
+                    let path = self
+                        .default_paths()
+                        .into_iter()
+                        .find(|p| p.is_file())
+                        .with_context(|| {
+                            format!("no {self} data file found, pass one explicitly")
+                        })?;
+                    Ok(fs::read_to_string(path)?)
+                }
+                _ => bail!("{self} needs a file (or - for stdin)"),
+            },
+        }
+    }
+
+    /// Where the tool keeps its data by default, most likely first.
+    fn default_paths(self) -> Vec<PathBuf> {
+        let home = dirs::home_dir().unwrap_or_default();
+        let config = dirs::config_dir().unwrap_or_else(|| home.join(".config"));
+
+        match self {
+            Self::VscodeProjectManager => ["Code", "Code - Insiders", "VSCodium"]
+                .iter()
+                .map(|app| {
+                    config
+                        .join(app)
+                        .join("User/globalStorage/alefragnani.project-manager/projects.json")
+                })
+                .collect(),
+            Self::Projectile => vec![
+                home.join(".emacs.d/projectile-bookmarks.eld"),
+                home.join(".emacs.d/.cache/projectile-bookmarks.eld"),
+                config.join("emacs/projectile-bookmarks.eld"),
+            ],
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn parse(self, input: &str) -> Result<Vec<ImportedProject>> {
+        match self {
+            Self::Zoxide => parse_zoxide(input),
+            Self::VscodeProjectManager => parse_vscode(input),
+            Self::Projectile => Ok(parse_projectile(input)),
+            Self::Ghq | Self::PlainList => Ok(parse_lines(input)),
+            Self::Json => parse_export(ExportFormat::Json, input),
+            Self::Toml => parse_export(ExportFormat::Toml, input),
+            Self::Csv => parse_export(ExportFormat::Csv, input),
+            Self::Markdown => parse_export(ExportFormat::Markdown, input),
+        }
+    }
+}
+
+/// Dumps written by `bvo export` carry everything but bare state.
+fn parse_export(format: ExportFormat, input: &str) -> Result<Vec<ImportedProject>> {
+    Ok(exporters::parse(format, input)?
+        .into_iter()
+        .map(|p| ImportedProject {
+            name: Some(p.name),
+            tags: p.tags,
+            visits: Some(p.visits),
+            added_at: p.added_at,
+            last_opened_at: p.last_opened_at,
+            aliases: p.aliases,
+            description: p.description,
+            notes: p.notes,
+            pinned: p.pinned,
+            archived: p.archived,
+            ..ImportedProject::at(&p.path.to_string_lossy())
+        })
+        .collect())
+}
+
+fn run(program: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .with_context(|| format!("failed to run {program}, is it installed?"))?;
+    if !output.status.success() {
+        bail!(
+            "{program} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Highest visit count taken from a zoxide score. Scores of busy directories
+/// run into the thousands and would otherwise outrank everything for months.
+const MAX_ZOXIDE_VISITS: f64 = 100.0;
+
+/// Lines of `<score> <path>`. The score is zoxide's own frecency and is kept,
+/// rounded and capped at [`MAX_ZOXIDE_VISITS`], as the visit count.
+fn parse_zoxide(input: &str) -> Result<Vec<ImportedProject>> {
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (score, path) = line
+                .trim_start()
+                .split_once(' ')
+                .with_context(|| format!("unexpected zoxide line: {line}"))?;
+            let score: f64 = score
+                .parse()
+                .ok()
+                .filter(|s: &f64| s.is_finite())
+                .with_context(|| format!("unexpected zoxide score: {score}"))?;
+
+            Ok(ImportedProject {
+                visits: Some(score.clamp(0.0, MAX_ZOXIDE_VISITS).round() as u32),
+                ..ImportedProject::at(path.trim())
+            })
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VscodeProject {
+    name: String,
+    root_path: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default = "enabled")]
+    enabled: bool,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+fn parse_vscode(input: &str) -> Result<Vec<ImportedProject>> {
+    let projects: Vec<VscodeProject> = serde_json::from_str(input)?;
+
+    Ok(projects
+        .into_iter()
+        .filter(|p| p.enabled)
+        .map(|p| {
+            // The extension writes `$home` for the home directory.
+            let path = p.root_path.replacen("$home", "~", 1);
+            ImportedProject {
+                name: Some(p.name),
+                tags: p.tags,
+                ..ImportedProject::at(&path)
+            }
+        })
+        .collect())
+}
+
+/// Projectile stores an elisp list of strings: `("~/code/a/" "/srv/b/")`.
+fn parse_projectile(input: &str) -> Vec<ImportedProject> {
+    let mut projects = Vec::new();
+    let mut chars = input.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '"' {
+            continue;
+        }
+
+        let mut path = String::new();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => path.extend(chars.next()),
+                c => path.push(c),
+            }
+        }
+        projects.push(ImportedProject::at(&path));
+    }
+
+    projects
+}
+
+fn parse_lines(input: &str) -> Vec<ImportedProject> {
+    input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(ImportedProject::at)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_zoxide() {
+        let input = "  12.5 /home/me/code/api\n   0.3 /tmp/with space\n";
+        let projects = ImportSource::Zoxide.parse(input).unwrap();
+
+        assert_eq!(projects.len(), 2);
+        assert_eq!(projects[0].path, PathBuf::from("/home/me/code/api"));
+        assert_eq!(projects[0].visits, Some(13));
+        assert_eq!(projects[1].path, PathBuf::from("/tmp/with space"));
+
+        assert!(ImportSource::Zoxide.parse("garbage\n").is_err());
+        assert!(ImportSource::Zoxide.parse("NaN /tmp/a\n").is_err());
+
+        let projects = ImportSource::Zoxide.parse("48213.7 /a\n-2 /b\n").unwrap();
+        assert_eq!(projects[0].visits, Some(MAX_ZOXIDE_VISITS as u32));
+        assert_eq!(projects[1].visits, Some(0));
+    }
+
+    #[test]
+    fn test_parse_vscode() {
+        let input = r#"[
+            { "name": "API", "rootPath": "/srv/api", "tags": ["work"], "enabled": true },
+            { "name": "Old", "rootPath": "/srv/old", "enabled": false },
+            { "name": "Dots", "rootPath": "$home/dotfiles" }
+        ]"#;
+        let projects = ImportSource::VscodeProjectManager.parse(input).unwrap();
+
+        assert_eq!(projects.len(), 2);
+        assert_eq!(projects[0].name.as_deref(), Some("API"));
+        assert_eq!(projects[0].tags, vec!["work"]);
+        assert_eq!(projects[1].path, expand_home(Path::new("~/dotfiles")));
+    }
+
+    #[test]
+    fn test_parse_projectile() {
+        let input = r#"("/srv/api/" "/tmp/quo\"te/")"#;
+        let paths: Vec<PathBuf> = ImportSource::Projectile
+            .parse(input)
+            .unwrap()
+            .into_iter()
+            .map(|p| p.path)
+            .collect();
+
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("/srv/api"), PathBuf::from("/tmp/quo\"te")]
+        );
+    }
+
+    #[test]
+    fn test_parse_plain_list() {
+        let input = "# work\n/srv/api\n\n  /srv/web/  \n";
+        let paths: Vec<PathBuf> = ImportSource::PlainList
+            .parse(input)
+            .unwrap()
+            .into_iter()
+            .map(|p| p.path)
+            .collect();
+
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("/srv/api"), PathBuf::from("/srv/web")]
+        );
+    }
+
+    #[test]
+    fn test_source_names() {
+        assert_eq!(
+            "vscode-project-manager".parse::<ImportSource>().unwrap(),
+            ImportSource::VscodeProjectManager
+        );
+        assert_eq!(ImportSource::PlainList.to_string(), "plain-list");
+    }
+}
@@ -1,11 +1,20 @@
 mod cli;
 mod config;
+mod exporters;
 mod git;
+mod importers;
+mod profile;
 mod project;
+mod ranking;
+mod remote;
+mod resolve;
 mod shell;
 mod storage;
+mod sync;
+mod tag_query;
 mod utils;
 mod version_check;
+mod visits;
 
 use anyhow::Result;
 use clap::Parser;
@@ -16,20 +25,125 @@ use storage::Storage;
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+    profile::select(cli.profile.clone())?;
 
     let check_process = tokio::spawn(async { version_check::is_update_available().await });
 
     match cli.command.clone() {
-        Command::Add { path, name, tags } => cli::commands::add(path, name, tags),
-        Command::List { tags, json } => cli::commands::list(tags, json),
-        Command::Pick { tags, query } => cli::commands::pick(query, tags),
-        Command::Remove { name, all, tags } => cli::commands::remove(name, tags, all),
+        Command::Add {
+            path,
+            name,
+            tags,
+            dry_run,
+        } => cli::commands::add(path, name, tags, dry_run),
+        Command::Scan {
+            root,
+            depth,
+            tags,
+            ignore,
+            yes,
+            dry_run,
+        } => cli::commands::scan(root, depth, tags, ignore, yes, dry_run),
+        Command::Import {
+            from,
+            file,
+            tags,
+            git_only,
+            yes,
+            dry_run,
+        } => cli::commands::import(from, file, tags, git_only, yes, dry_run),
+        Command::Export {
+            format,
+            tags,
+            output,
+        } => cli::commands::export(format, tags, output),
+        Command::Sync { remote } => cli::commands::sync(remote),
+        Command::Clone {
+            url,
+            name,
+            tags,
+            bare,
+            root,
+            dry_run,
+        } => cli::commands::clone(url, name, tags, bare, root, dry_run),
+        Command::List {
+            selection,
+            json,
+            status,
+            notes,
+        } => cli::commands::list(selection, json, status, notes),
+        Command::Pick {
+            selection,
+            query,
+            preview,
+            first,
+            worktree,
+            search_notes,
+        } => cli::commands::pick(query, selection, preview, first, worktree, search_notes),
+        Command::Rank { selection, explain } => cli::commands::rank(selection, explain),
+        Command::Recent {
+            selection,
+            limit,
+            json,
+        } => cli::commands::recent(selection, limit, json),
+        Command::Preview { name } => cli::commands::preview(name),
+        Command::Path {
+            query,
+            tags,
+            worktree,
+            first,
+        } => cli::commands::path(query, tags, worktree, first),
+        Command::Remove {
+            name,
+            all,
+            tags,
+            dry_run,
+        } => cli::commands::remove(name, tags, all, dry_run),
         Command::Tag {
             project,
             tags,
             remove,
-        } => cli::commands::tag(project, tags, remove),
+            dry_run,
+        } => cli::commands::tag(project, tags, remove, dry_run),
+        Command::Alias {
+            project,
+            aliases,
+            remove,
+            dry_run,
+        } => cli::commands::alias(project, aliases, remove, dry_run),
+        Command::Pin { name, dry_run } => cli::commands::pin(name, true, dry_run),
+        Command::Unpin { name, dry_run } => cli::commands::pin(name, false, dry_run),
+        Command::Archive { name, dry_run } => cli::commands::archive(name, true, dry_run),
+        Command::Unarchive { name, dry_run } => cli::commands::archive(name, false, dry_run),
+        Command::Describe {
+            name,
+            description,
+            readme,
+            clear,
+            dry_run,
+        } => cli::commands::describe(name, description, readme, clear, dry_run),
+        Command::Note {
+            name,
+            print,
+            clear,
+            dry_run,
+        } => cli::commands::note(name, print, clear, dry_run),
+        Command::Undo { count, dry_run } => cli::commands::undo(count, dry_run),
+        Command::History { limit, json } => cli::commands::history(limit, json),
+        Command::Rename {
+            name,
+            new_name,
+            dry_run,
+        } => cli::commands::rename(name, new_name, dry_run),
+        Command::Mv {
+            name,
+            path,
+            move_files,
+            dry_run,
+        } => cli::commands::mv(name, path, move_files, dry_run),
+        Command::Doctor { check, dry_run } => cli::commands::doctor(check, dry_run),
         Command::Config { action } => cli::commands::config(action),
+        Command::Wt { action } => cli::commands::worktree(action),
         Command::CheckUpdate => {
             if let Some(v) = version_check::is_update_available().await? {
                 println!("A new version is available: {v}")
@@ -40,9 +154,19 @@ async fn main() -> Result<()> {
             Ok(())
         }
         Command::Init { shell } => cmd_init(shell),
+        Command::Complete => cli::commands::complete(),
     }?;
 
-    if !matches!(cli.command, Command::CheckUpdate) {
+    // `path`, `preview`, `export` and `complete` output is consumed by other
+    // programs, so it never gets the update notice.
+    if !matches!(
+        cli.command,
+        Command::CheckUpdate
+            | Command::Path { .. }
+            | Command::Preview { .. }
+            | Command::Export { .. }
+            | Command::Complete
+    ) {
         if let Some(latest) = check_process.await?? {
             println!();
             println!("A new update is available: {latest}");
@@ -0,0 +1,100 @@
+use crate::config::{config_dir, Config};
+use anyhow::{bail, Result};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::{fs, io};
+
+/// Profile used when none is selected; its registry is `projects.json` in
+/// the config directory.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Environment variable selecting the profile when `--profile` is not given.
+pub const PROFILE_ENV: &str = "BVO_PROFILE";
+
+static SELECTED: OnceLock<String> = OnceLock::new();
+
+/// Settles the profile for the rest of the process: `--profile`, then
+/// `BVO_PROFILE`, then the `default_profile` config key.
+pub fn select(flag: Option<String>) -> Result<()> {
+    let profile = flag
+        .or_else(|| std::env::var(PROFILE_ENV).ok().filter(|p| !p.is_empty()))
+        .or_else(|| Config::load().ok().and_then(|c| c.default_profile))
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+    validate(&profile)?;
+    let _ = SELECTED.set(profile);
+
+    Ok(())
+}
+
+/// The profile commands operate on, see [`select`].
+pub fn active() -> &'static str {
+    SELECTED.get().map_or(DEFAULT_PROFILE, String::as_str)
+}
+
+/// Registry file of `profile`. Named profiles get a directory of their own so
+/// their history, lock and sync repository stay apart.
+pub fn registry_path(profile: &str) -> PathBuf {
+    if profile == DEFAULT_PROFILE {
+        config_dir().join("projects.json")
+    } else {
+        config_dir()
+            .join("profiles")
+            .join(profile)
+            .join("projects.json")
+    }
+}
+
+/// The default profile and every profile with a registry, sorted by name.
+pub fn all() -> io::Result<Vec<String>> {
+    let mut profiles = vec![DEFAULT_PROFILE.to_string()];
+
+    if let Ok(entries) = fs::read_dir(config_dir().join("profiles")) {
+        for entry in entries {
+            let entry = entry?;
+            if entry.path().join("projects.json").exists() {
+                profiles.push(entry.file_name().to_string_lossy().into_owned());
+            }
+        }
+    }
+    profiles[1..].sort();
+
+    Ok(profiles)
+}
+
+/// Profile names become directory names and prefix entries as `profile:name`.
+pub fn validate(profile: &str) -> Result<()> {
+    let valid = !profile.is_empty()
+        && profile
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        bail!("invalid profile name '{profile}': use letters, digits, '-' and '_'");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_path() {
+        assert_eq!(
+            registry_path(DEFAULT_PROFILE),
+            config_dir().join("projects.json")
+        );
+        assert_eq!(
+            registry_path("work"),
+            config_dir().join("profiles/work/projects.json")
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate("work").is_ok());
+        assert!(validate("side_projects-2").is_ok());
+        assert!(validate("").is_err());
+        assert!(validate("../etc").is_err());
+        assert!(validate("a:b").is_err());
+    }
+}
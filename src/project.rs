@@ -1,6 +1,12 @@
+use crate::ranking::Ranking;
+use crate::visits::VisitLog;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::{fmt, path::PathBuf};
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Project {
@@ -16,10 +22,37 @@ pub struct Project {
     pub last_opened_at: DateTime<Utc>,
     #[serde(default)]
     pub visits: u32,
+    /// Short names resolved exactly, like the name itself.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// One-line summary, seeded from the README when the project is added.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Free-form notes, edited with `bvo note`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// Pinned projects sort before all others.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+    /// Archived projects are hidden from `list` and `pick` unless asked for.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub archived: bool,
+    /// When the project was opened, see [`VisitLog`].
+    #[serde(default, skip_serializing_if = "VisitLog::is_empty")]
+    pub visit_log: VisitLog,
+    /// When each worktree of a bare repository was opened, by worktree name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub worktree_visits: BTreeMap<String, VisitLog>,
+    /// Profile the project was loaded from when listing across profiles.
+    #[serde(skip)]
+    pub profile: Option<String>,
 }
 
 impl fmt::Display for Project {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(profile) = &self.profile {
+            write!(f, "{profile}:")?;
+        }
         if self.is_bare_repo {
             return write!(f, "{} (bare)", self.name);
         }
@@ -38,12 +71,109 @@ impl Project {
             added_at: Utc::now(),
             last_opened_at: Utc::now(),
             visits: 0,
+            aliases: Vec::new(),
+            description: None,
+            notes: None,
+            pinned: false,
+            archived: false,
+            visit_log: VisitLog::default(),
+            worktree_visits: BTreeMap::new(),
+            profile: None,
         }
     }
 
+    /// Name used when none is given: the last component of `path`.
+    pub fn default_name(path: &Path) -> String {
+        path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown")
+            .to_string()
+    }
+
+    /// Contents of the first README found in the project directory.
+    pub fn readme(&self) -> Option<String> {
+        README_NAMES
+            .iter()
+            .find_map(|name| fs::read_to_string(self.path.join(name)).ok())
+    }
+
+    /// Sets the description from the README unless one is already set.
+    pub fn with_readme_description(mut self) -> Self {
+        if self.description.is_none() {
+            self.description = self.readme().as_deref().and_then(first_paragraph);
+        }
+        self
+    }
+
+    /// Whether `query` is exactly the name or one of the aliases.
+    pub fn is_called(&self, query: &str) -> bool {
+        self.name == query || self.aliases.iter().any(|a| a == query)
+    }
+
+    /// Text matched by pickers searching descriptions and notes too.
+    pub fn search_text(&self) -> String {
+        let aliases = self.aliases.join(" ");
+        [
+            Some(self.name.as_str()),
+            Some(aliases.as_str()).filter(|a| !a.is_empty()),
+            self.description.as_deref(),
+            self.notes.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ")
+    }
+
     pub fn on_access(&mut self) {
         self.last_opened_at = Utc::now();
         self.visits += 1;
+        self.visit_log.record(self.last_opened_at);
+    }
+
+    /// Records a visit to the project through its worktree `name`. Only the
+    /// [`MAX_WORKTREE_LOGS`] most recently used worktrees keep a log.
+    pub fn on_worktree_access(&mut self, name: &str) {
+        self.on_access();
+        self.worktree_visits
+            .entry(name.to_string())
+            .or_default()
+            .record(self.last_opened_at);
+
+        while self.worktree_visits.len() > MAX_WORKTREE_LOGS {
+            let stalest = self
+                .worktree_visits
+                .iter()
+                .min_by_key(|(_, log)| log.last())
+                .map(|(name, _)| name.clone());
+            if let Some(stalest) = stalest {
+                self.worktree_visits.remove(&stalest);
+            }
+        }
+    }
+
+    /// Score of worktree `name` under `ranking`, zero when it was never
+    /// opened.
+    pub fn worktree_frecency(&self, name: &str, ranking: &Ranking, now: DateTime<Utc>) -> f64 {
+        self.worktree_visits
+            .get(name)
+            .map_or(0.0, |log| ranking.log_score(log, now))
+    }
+
+    /// Name of the worktree opened most recently.
+    pub fn last_worktree(&self) -> Option<&str> {
+        self.worktree_visits
+            .iter()
+            .filter_map(|(name, log)| Some((name, log.last()?)))
+            .max_by_key(|(_, at)| *at)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Visits counted before the log existed or carried over by an import,
+    /// which are taken to have happened at `last_opened_at`.
+    pub fn untracked_visits(&self) -> u32 {
+        let tracked = u32::try_from(self.visit_log.total()).unwrap_or(u32::MAX);
+        self.visits.saturating_sub(tracked)
     }
 
     pub fn with_tags(mut self, tags: Vec<String>) -> Self {
@@ -65,39 +195,53 @@ impl Project {
         self.tags.iter().any(|t| t == tag)
     }
 
-    pub fn has_any_tag(&self, tags: &[String]) -> bool {
-        tags.iter().any(|t| self.has_tag(t))
-    }
-
     pub fn exists(&self) -> bool {
-        self.path.try_exists().is_ok()
-    }
-
-    fn frecency(&self) -> f64 {
-        let now = Utc::now();
-        let dx = (now - self.last_opened_at).as_seconds_f64();
-        let rank = self.visits as f64 + 1.0;
-        match dx {
-            d if d < 3_600. => rank * 4.0,
-            d if d < 86_400. => rank * 2.0,
-            d if d < 604_800. => rank * 2.0,
-            _ => rank / 4.0,
-        }
+        self.path.try_exists().unwrap_or(false)
     }
 }
 
-impl PartialOrd for Project {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+/// Worktrees of one project that keep a visit log.
+pub const MAX_WORKTREE_LOGS: usize = 32;
+
+/// README file names, in order of preference.
+pub const README_NAMES: [&str; 5] = [
+    "README.md",
+    "README",
+    "readme.md",
+    "README.rst",
+    "README.txt",
+];
+
+/// Longest description taken from a README, in characters.
+const MAX_DESCRIPTION_LEN: usize = 200;
+
+/// First paragraph of prose in a README, skipping headings, badges, HTML and
+/// reStructuredText underlines, joined into one line.
+fn first_paragraph(readme: &str) -> Option<String> {
+    let is_prose = |line: &str| {
+        !(line.starts_with('#')
+            || line.starts_with("![")
+            || line.starts_with("[![")
+            || line.starts_with('<')
+            || line.chars().all(|c| "=-~*".contains(c)))
+    };
+
+    let paragraph: Vec<&str> = readme
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| line.is_empty() || !is_prose(line))
+        .take_while(|line| !line.is_empty() && is_prose(line))
+        .collect();
+    if paragraph.is_empty() {
+        return None;
     }
-}
 
-impl Ord for Project {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.frecency()
-            .total_cmp(&other.frecency())
-            .then_with(|| self.added_at.cmp(&other.added_at))
+    let text = paragraph.join(" ");
+    if text.chars().count() <= MAX_DESCRIPTION_LEN {
+        return Some(text);
     }
+    let truncated: String = text.chars().take(MAX_DESCRIPTION_LEN - 1).collect();
+    Some(format!("{}…", truncated.trim_end()))
 }
 
 #[cfg(test)]
@@ -148,10 +292,74 @@ mod tests {
     }
 
     #[test]
-    fn test_has_any_tag() {
-        let project = sample_project().with_tags(vec!["rust".to_string(), "cli".to_string()]);
-        assert!(project.has_any_tag(&["rust".to_string()]));
-        assert!(project.has_any_tag(&["python".to_string(), "cli".to_string()]));
-        assert!(!project.has_any_tag(&["python".to_string(), "js".to_string()]));
+    fn test_exists() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut project = sample_project();
+        project.path = dir.path().to_path_buf();
+        assert!(project.exists());
+
+        project.path = dir.path().join("missing");
+        assert!(!project.exists());
+    }
+
+    #[test]
+    fn test_readme_description() {
+        let readme = indoc::indoc! {"
+            # Bivio
+
+            [![CI](https://example.com/badge.svg)](https://example.com)
+
+            A fast project navigator
+            with tags.
+
+            ## Install
+        "};
+        assert_eq!(
+            first_paragraph(readme).as_deref(),
+            Some("A fast project navigator with tags.")
+        );
+        assert_eq!(first_paragraph("# Title only\n"), None);
+
+        let long = "word ".repeat(100);
+        let description = first_paragraph(&long).unwrap();
+        assert_eq!(description.chars().count(), MAX_DESCRIPTION_LEN);
+        assert!(description.ends_with('…'));
+    }
+
+    #[test]
+    fn test_worktree_visits() {
+        let now = Utc::now();
+        let mut project = sample_project();
+        assert_eq!(project.last_worktree(), None);
+
+        let log = project
+            .worktree_visits
+            .entry("main".to_string())
+            .or_default();
+        for hours in 1..=5 {
+            log.record(now - chrono::TimeDelta::hours(hours));
+        }
+        project.on_worktree_access("feature");
+
+        assert_eq!(project.last_worktree(), Some("feature"));
+        let (ranking, now) = (Ranking::default(), Utc::now());
+        assert!(
+            project.worktree_frecency("main", &ranking, now)
+                > project.worktree_frecency("feature", &ranking, now)
+        );
+        assert_eq!(project.worktree_frecency("gone", &ranking, now), 0.0);
+        assert_eq!(project.visits, 1);
+    }
+
+    #[test]
+    fn test_search_text() {
+        let mut project = sample_project();
+        project.description = Some("CLI for invoices".to_string());
+        assert_eq!(project.search_text(), "test-project CLI for invoices");
+
+        project.aliases = vec!["tp".to_string()];
+        assert_eq!(project.search_text(), "test-project tp CLI for invoices");
+        assert!(project.is_called("tp"));
+        assert!(!project.is_called("test"));
     }
 }
@@ -0,0 +1,302 @@
+use crate::config::Config;
+use crate::visits::VisitLog;
+use crate::Project;
+use chrono::{DateTime, Datelike, Local, TimeDelta, Timelike, Utc, Weekday};
+use std::borrow::Borrow;
+use std::sync::OnceLock;
+
+/// Days after which a visit counts half as much, unless configured.
+pub const DEFAULT_HALF_LIFE_DAYS: f64 = 7.0;
+
+/// Total visits across a registry above which every count is aged.
+pub const DEFAULT_MAX_VISITS: u32 = 10_000;
+
+/// Logged visits needed before the time of day and weekday count.
+const MIN_CONTEXT_VISITS: usize = 5;
+
+static CURRENT: OnceLock<Ranking> = OnceLock::new();
+
+/// Frecency model. Every logged visit adds `0.5^(age / half_life)`; visits
+/// outside the log (see [`Project::untracked_visits`]) and a base of one
+/// count as made at `last_opened_at`. With `context`, the sum is boosted by
+/// up to 2x when the project is usually opened around this time of day on
+/// this kind of day.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ranking {
+    pub half_life_days: f64,
+    pub max_visits: u32,
+    pub context: bool,
+}
+
+/// How a project's score was computed, as shown by `bvo rank --explain`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Score {
+    pub visits: u32,
+    /// Time since the project was last opened.
+    pub age: TimeDelta,
+    /// Weight of a visit made at `last_opened_at`.
+    pub decay: f64,
+    /// Decayed sum of the logged visits.
+    pub history: f64,
+    pub context: f64,
+    pub value: f64,
+}
+
+impl Default for Ranking {
+    fn default() -> Self {
+        Self {
+            half_life_days: DEFAULT_HALF_LIFE_DAYS,
+            max_visits: DEFAULT_MAX_VISITS,
+            context: true,
+        }
+    }
+}
+
+impl Ranking {
+    pub fn from_config(config: &Config) -> Self {
+        let default = Self::default();
+        Self {
+            half_life_days: config
+                .frecency_half_life_days
+                .unwrap_or(default.half_life_days),
+            max_visits: config.frecency_max_visits.unwrap_or(default.max_visits),
+            context: config.frecency_context.unwrap_or(default.context),
+        }
+    }
+
+    /// The model configured for this process, read once.
+    pub fn current() -> Self {
+        // Like the registry, ranking falls back to defaults on a broken
+        // config rather than failing every comparison.
+        *CURRENT.get_or_init(|| Self::from_config(&Config::load().unwrap_or_default()))
+    }
+
+    pub fn score(&self, project: &Project, now: DateTime<Utc>) -> Score {
+        let decay = self.decay(project.last_opened_at, now);
+        let history = project.visit_log.weighted(|at| self.decay(at, now));
+        let context = if self.context {
+            context(&project.visit_log, now)
+        } else {
+            1.0
+        };
+        let untracked = f64::from(project.untracked_visits());
+
+        Score {
+            visits: project.visits,
+            // Clock skew between synced machines can put the last open in the
+            // future; treat it as just now.
+            age: (now - project.last_opened_at).max(TimeDelta::zero()),
+            decay,
+            history,
+            context,
+            value: ((untracked + 1.0) * decay + history) * context,
+        }
+    }
+
+    /// `projects` best first: pinned ones, then by score, then the most
+    /// recently added. Each score is computed once, all at the same `now`.
+    pub fn ranked<P: Borrow<Project>>(&self, projects: Vec<P>, now: DateTime<Utc>) -> Vec<P> {
+        let mut keyed: Vec<(f64, P)> = projects
+            .into_iter()
+            .map(|p| (self.score(p.borrow(), now).value, p))
+            .collect();
+        keyed.sort_by(|(a_score, a), (b_score, b)| {
+            let (a, b) = (a.borrow(), b.borrow());
+            b.pinned
+                .cmp(&a.pinned)
+                .then_with(|| b_score.total_cmp(a_score))
+                .then_with(|| b.added_at.cmp(&a.added_at))
+        });
+
+        keyed.into_iter().map(|(_, p)| p).collect()
+    }
+
+    /// Score of a bare project's worktree: the decayed sum of its visits,
+    /// boosted by context like a project.
+    pub fn log_score(&self, log: &VisitLog, now: DateTime<Utc>) -> f64 {
+        let context = if self.context { context(log, now) } else { 1.0 };
+        log.weighted(|at| self.decay(at, now)) * context
+    }
+
+    /// Weight of a visit made `at`, halving every half-life.
+    fn decay(&self, at: DateTime<Utc>, now: DateTime<Utc>) -> f64 {
+        let age_days = (now - at).max(TimeDelta::zero()).as_seconds_f64() / 86_400.;
+        0.5_f64.powf(age_days / self.half_life_days)
+    }
+
+    /// Ages visit counts the way zoxide does: once their total exceeds
+    /// `max_visits`, every count is scaled so the total drops to 90% of it.
+    /// The visit logs are scaled alike, and a count never drops below what its
+    /// log holds after rounding. Returns whether anything was aged.
+    pub fn age_visits(&self, projects: &mut [Project]) -> bool {
+        let total: u64 = projects.iter().map(|p| u64::from(p.visits)).sum();
+        if total <= u64::from(self.max_visits) {
+            return false;
+        }
+
+        let factor = 0.9 * f64::from(self.max_visits) / total as f64;
+        for project in projects {
+            project.visits = (f64::from(project.visits) * factor).round() as u32;
+            project.visit_log.age(factor);
+            project.visits = project
+                .visits
+                .max(u32::try_from(project.visit_log.total()).unwrap_or(u32::MAX));
+            for log in project.worktree_visits.values_mut() {
+                log.age(factor);
+            }
+        }
+        true
+    }
+}
+
+/// `1 +` the share of logged visits made within an hour of the local time of
+/// day of `now`, on the same kind of day (weekday or weekend).
+fn context(log: &VisitLog, now: DateTime<Utc>) -> f64 {
+    let visits: Vec<DateTime<Local>> = log.recent().map(|t| t.with_timezone(&Local)).collect();
+    if visits.len() < MIN_CONTEXT_VISITS {
+        return 1.0;
+    }
+
+    let now = now.with_timezone(&Local);
+    let is_weekend = |day: Weekday| matches!(day, Weekday::Sat | Weekday::Sun);
+    let matching = visits
+        .iter()
+        .filter(|visit| {
+            let hours = visit.hour().abs_diff(now.hour());
+            hours.min(24 - hours) <= 1 && is_weekend(visit.weekday()) == is_weekend(now.weekday())
+        })
+        .count();
+
+    1.0 + matching as f64 / visits.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn project(visits: u32, days_ago: i64, now: DateTime<Utc>) -> Project {
+        let mut project = Project::new("p".to_string(), PathBuf::from("/tmp/p"), false);
+        project.visits = visits;
+        project.last_opened_at = now - TimeDelta::days(days_ago);
+        project
+    }
+
+    #[test]
+    fn test_score_decays_by_half_life() {
+        let ranking = Ranking::default();
+        let now = Utc::now();
+
+        assert_eq!(ranking.score(&project(3, 0, now), now).value, 4.0);
+        assert_eq!(ranking.score(&project(3, 7, now), now).value, 2.0);
+        assert_eq!(ranking.score(&project(3, 14, now), now).value, 1.0);
+
+        // Old heavy use loses to recent work.
+        let stale = ranking.score(&project(1_000, 730, now), now);
+        let recent = ranking.score(&project(2, 1, now), now);
+        assert!(recent.value > stale.value);
+    }
+
+    #[test]
+    fn test_score_is_continuous() {
+        let ranking = Ranking::default();
+        let now = Utc::now();
+        let scores: Vec<f64> = (0..10)
+            .map(|days| ranking.score(&project(5, days, now), now).value)
+            .collect();
+
+        assert!(scores.windows(2).all(|w| w[0] > w[1]));
+    }
+
+    #[test]
+    fn test_age_visits_over_cap() {
+        let ranking = Ranking {
+            max_visits: 100,
+            ..Ranking::default()
+        };
+        let now = Utc::now();
+        let mut projects = vec![project(60, 0, now), project(40, 0, now)];
+        assert!(!ranking.age_visits(&mut projects));
+
+        projects[0].visits = 110;
+        projects[1].visits = 40;
+        assert!(ranking.age_visits(&mut projects));
+        assert_eq!(projects[0].visits, 66);
+        assert_eq!(projects[1].visits, 24);
+    }
+
+    #[test]
+    fn test_age_visits_keeps_log_within_count() {
+        let ranking = Ranking {
+            max_visits: 100,
+            ..Ranking::default()
+        };
+        let now = Utc::now();
+        let mut projects = vec![project(0, 0, now), project(0, 0, now)];
+        for (i, project) in projects.iter_mut().enumerate() {
+            for days in 0..(60 + i as i64 * 7) {
+                project.visit_log.record(now - TimeDelta::days(days));
+                project.visits += 1;
+            }
+        }
+
+        assert!(ranking.age_visits(&mut projects));
+        for project in &projects {
+            assert!(u64::from(project.visits) >= project.visit_log.total());
+            assert!(project.visits < 67);
+        }
+    }
+
+    #[test]
+    fn test_ranked_puts_pinned_first() {
+        let ranking = Ranking::default();
+        let now = Utc::now();
+        let mut busy = project(100, 0, now);
+        busy.name = "busy".to_string();
+        let mut pinned = project(0, 30, now);
+        pinned.name = "pinned".to_string();
+        pinned.pinned = true;
+        let mut idle = project(0, 30, now);
+        idle.name = "idle".to_string();
+
+        let ranked = ranking.ranked(vec![&idle, &busy, &pinned], now);
+        let names: Vec<&str> = ranked.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["pinned", "busy", "idle"]);
+    }
+
+    #[test]
+    fn test_score_sums_logged_visits() {
+        let ranking = Ranking {
+            context: false,
+            ..Ranking::default()
+        };
+        let now = Utc::now();
+        let mut logged = project(0, 0, now);
+        for days in [7, 14] {
+            logged.visit_log.record(now - TimeDelta::days(days));
+            logged.visits += 1;
+        }
+
+        // Base of one at the last open, plus 0.5 and 0.25 for the visits.
+        let score = ranking.score(&logged, now);
+        assert!((score.history - 0.75).abs() < 1e-6);
+        assert!((score.value - 1.75).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_context_favours_usual_time() {
+        let ranking = Ranking::default();
+        let now = Utc::now();
+        let mut weekly = project(0, 0, now);
+        let mut off_hours = project(0, 0, now);
+        for week in 1..=6 {
+            weekly.visit_log.record(now - TimeDelta::weeks(week));
+            off_hours
+                .visit_log
+                .record(now - TimeDelta::weeks(week) - TimeDelta::hours(12));
+        }
+
+        assert_eq!(ranking.score(&weekly, now).context, 2.0);
+        assert_eq!(ranking.score(&off_hours, now).context, 1.0);
+    }
+}
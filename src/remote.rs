@@ -0,0 +1,182 @@
+use anyhow::{bail, Result};
+use std::path::{Component, Path, PathBuf};
+
+/// Host used in the clone layout for `file://` and local-path remotes.
+pub const LOCAL_HOST: &str = "local";
+
+/// The parts of a remote URL used to build the clone destination.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteUrl {
+    pub host: String,
+    pub owner: String,
+    pub repo: String,
+}
+
+impl RemoteUrl {
+    /// Parses `https://`, `ssh://`, scp-like (`git@host:owner/repo`),
+    /// `file://` and plain local-path remotes.
+    pub fn parse(url: &str) -> Result<Self> {
+        let url = url.trim().trim_end_matches('/');
+
+        let (host, path) = if Path::new(url).exists() {
+            (LOCAL_HOST.to_string(), local_path(url))
+        } else if let Some(path) = url.strip_prefix("file://") {
+            (LOCAL_HOST.to_string(), path.to_string())
+        } else if let Some((_, rest)) = url.split_once("://") {
+            let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+            (strip_authority(authority), path.to_string())
+        } else if let Some((authority, path)) = scp_like(url) {
+            (strip_authority(authority), path.to_string())
+        } else {
+            bail!("unsupported remote url: {url}");
+        };
+
+        // Every part ends up as a directory of the clone destination, so
+        // none may climb out of it or be empty.
+        let path = path.trim_start_matches('/');
+        if path.is_empty() {
+            bail!("remote url has no repository name: {url}");
+        }
+        let mut segments: Vec<&str> = path.split('/').collect();
+        for segment in std::iter::once(host.as_str()).chain(segments.iter().copied()) {
+            if !is_plain_segment(segment) {
+                bail!("invalid path segment '{segment}' in remote url: {url}");
+            }
+        }
+        let Some(repo) = segments.pop().map(|r| r.trim_end_matches(".git")) else {
+            bail!("remote url has no repository name: {url}");
+        };
+        if !is_plain_segment(repo) {
+            bail!("remote url has no repository name: {url}");
+        }
+
+        // Local paths only keep the parent directory, not the whole path.
+        let owner = if host == LOCAL_HOST {
+            segments.last().map(|s| s.to_string()).unwrap_or_default()
+        } else {
+            segments.join("/")
+        };
+
+        Ok(Self {
+            host,
+            owner,
+            repo: repo.to_string(),
+        })
+    }
+
+    /// Expands a layout such as `{root}/{host}/{owner}/{repo}`.
+    pub fn destination(&self, layout: &str, root: &Path) -> PathBuf {
+        let expanded = layout
+            .replace("{root}", &root.to_string_lossy())
+            .replace("{host}", &self.host)
+            .replace("{owner}", &self.owner)
+            .replace("{repo}", &self.repo);
+
+        PathBuf::from(expanded).components().collect()
+    }
+}
+
+/// Whether `segment` is a single plain directory name: not empty, `.`, `..`
+/// or absolute.
+fn is_plain_segment(segment: &str) -> bool {
+    let mut components = Path::new(segment).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    )
+}
+
+fn local_path(path: &str) -> String {
+    std::fs::canonicalize(path)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| path.to_string())
+}
+
+/// Drops the `user@` prefix and `:port` suffix from a URL authority.
+fn strip_authority(authority: &str) -> String {
+    let host = authority.rsplit('@').next().unwrap_or(authority);
+    host.split(':').next().unwrap_or(host).to_string()
+}
+
+/// Splits `[user@]host:path`, the scp-like syntax understood by git.
+fn scp_like(url: &str) -> Option<(&str, &str)> {
+    let (authority, path) = url.split_once(':')?;
+    if authority.len() < 2 || authority.contains('/') || path.starts_with("//") {
+        return None;
+    }
+    Some((authority, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn remote(host: &str, owner: &str, repo: &str) -> RemoteUrl {
+        RemoteUrl {
+            host: host.to_string(),
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_https() {
+        assert_eq!(
+            RemoteUrl::parse("https://github.com/rawnly/bivio.git").unwrap(),
+            remote("github.com", "rawnly", "bivio")
+        );
+        assert_eq!(
+            RemoteUrl::parse("https://gitlab.com/group/sub/app/").unwrap(),
+            remote("gitlab.com", "group/sub", "app")
+        );
+    }
+
+    #[test]
+    fn test_parse_ssh() {
+        assert_eq!(
+            RemoteUrl::parse("git@github.com:rawnly/bivio.git").unwrap(),
+            remote("github.com", "rawnly", "bivio")
+        );
+        assert_eq!(
+            RemoteUrl::parse("ssh://git@example.com:2222/team/app.git").unwrap(),
+            remote("example.com", "team", "app")
+        );
+    }
+
+    #[test]
+    fn test_parse_file() {
+        assert_eq!(
+            RemoteUrl::parse("file:///srv/git/team/app.git").unwrap(),
+            remote(LOCAL_HOST, "team", "app")
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(RemoteUrl::parse("not a url").is_err());
+        assert!(RemoteUrl::parse("https://github.com").is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_escaping_segments() {
+        assert!(RemoteUrl::parse("https://github.com/../../etc/app").is_err());
+        assert!(RemoteUrl::parse("https://github.com/owner/..").is_err());
+        assert!(RemoteUrl::parse("https://github.com/owner//app").is_err());
+        assert!(RemoteUrl::parse("git@github.com:./app.git").is_err());
+        assert!(RemoteUrl::parse("ssh://../owner/app").is_err());
+        assert!(RemoteUrl::parse("https://github.com/owner/.git").is_err());
+    }
+
+    #[test]
+    fn test_destination() {
+        let remote = remote("github.com", "rawnly", "bivio");
+        assert_eq!(
+            remote.destination("{root}/{host}/{owner}/{repo}", Path::new("/home/me/code")),
+            PathBuf::from("/home/me/code/github.com/rawnly/bivio")
+        );
+        assert_eq!(
+            remote.destination("{root}/{repo}", Path::new("/code/")),
+            PathBuf::from("/code/bivio")
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{git::Worktree, ranking::Ranking, Project};
+use chrono::Utc;
+
+/// Exit code used when no project (or worktree) matches the query.
+pub const EXIT_NOT_FOUND: i32 = 3;
+/// Exit code used when several projects match and none was preferred.
+pub const EXIT_AMBIGUOUS: i32 = 4;
+
+#[derive(Debug)]
+pub enum Resolution<'a> {
+    Found(&'a Project),
+    NotFound,
+    /// Candidates ordered by score, best first.
+    Ambiguous(Vec<&'a Project>),
+}
+
+impl<'a> Resolution<'a> {
+    /// Collapses an ambiguous resolution to its best candidate.
+    pub fn first(self) -> Option<&'a Project> {
+        match self {
+            Self::Found(project) => Some(project),
+            Self::NotFound => None,
+            Self::Ambiguous(candidates) => candidates.into_iter().next(),
+        }
+    }
+}
+
+/// Resolves `query` without prompting: an exact name or alias match wins,
+/// otherwise fuzzy matches on the name are ranked by match score weighted by
+/// frecency.
+pub fn resolve<'a>(projects: &[&'a Project], query: &str) -> Resolution<'a> {
+    if let Some(project) = find_exact(projects.iter().copied(), query) {
+        return Resolution::Found(project);
+    }
+
+    let ranking = Ranking::current();
+    let now = Utc::now();
+    let needle = query.replace(' ', "");
+    let names: Vec<&str> = projects.iter().map(|p| p.name.as_str()).collect();
+    let mut scored: Vec<(&Project, f64)> =
+        frizbee::match_list(&needle, &names, &frizbee::Config::default())
+            .into_iter()
+            .map(|m| {
+                let project = projects[m.index as usize];
+                let frecency = ranking.score(project, now).value;
+                (project, m.score as f64 * weight(frecency))
+            })
+            .collect();
+
+    match scored.len() {
+        0 => Resolution::NotFound,
+        1 => Resolution::Found(scored[0].0),
+        _ => {
+            scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+            Resolution::Ambiguous(scored.into_iter().map(|(p, _)| p).collect())
+        }
+    }
+}
+
+/// Project named `query`, or else the one with `query` as an alias.
+pub fn find_exact<'a>(
+    projects: impl Iterator<Item = &'a Project> + Clone,
+    query: &str,
+) -> Option<&'a Project> {
+    projects
+        .clone()
+        .find(|p| p.name == query)
+        .or_else(|| projects.into_iter().find(|p| p.is_called(query)))
+}
+
+/// Frecency grows without bound, so it is dampened to keep the match score
+/// the dominant signal.
+fn weight(frecency: f64) -> f64 {
+    1.0 + frecency.ln_1p()
+}
+
+/// Finds a worktree by name or by branch (with or without `refs/heads/`).
+pub fn find_worktree(worktrees: Vec<Worktree>, query: &str) -> Option<Worktree> {
+    worktrees
+        .into_iter()
+        .find(|wt| wt.name == query || wt.short_branch() == Some(query))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn project(name: &str, visits: u32) -> Project {
+        let mut project = Project::new(name.to_string(), PathBuf::from("/tmp").join(name), false);
+        project.visits = visits;
+        project
+    }
+
+    #[test]
+    fn test_exact_name_wins() {
+        let a = project("api", 0);
+        let b = project("api-gateway", 100);
+        let resolution = resolve(&[&b, &a], "api");
+        assert!(matches!(resolution, Resolution::Found(p) if p.name == "api"));
+    }
+
+    #[test]
+    fn test_alias_wins_over_fuzzy_match() {
+        let mut fe = project("frontend-monorepo", 0);
+        fe.aliases = vec!["fe".to_string()];
+        let b = project("fetch-service", 100);
+        let resolution = resolve(&[&b, &fe], "fe");
+        assert!(matches!(resolution, Resolution::Found(p) if p.name == "frontend-monorepo"));
+    }
+
+    #[test]
+    fn test_not_found() {
+        let a = project("api", 0);
+        assert!(matches!(resolve(&[&a], "zzz"), Resolution::NotFound));
+    }
+
+    #[test]
+    fn test_ambiguous_prefers_frecent() {
+        let a = project("web-admin", 0);
+        let b = project("web-app", 50);
+        let resolution = resolve(&[&a, &b], "web");
+        let Resolution::Ambiguous(candidates) = resolution else {
+            panic!("expected ambiguous resolution");
+        };
+        assert_eq!(candidates[0].name, "web-app");
+        assert_eq!(candidates.len(), 2);
+    }
+
+    #[test]
+    fn test_find_worktree() {
+        let wt = |name: &str, branch: &str| Worktree {
+            name: name.to_string(),
+            path: PathBuf::from("/tmp").join(name),
+            branch: Some(format!("refs/heads/{branch}")),
+        };
+        let worktrees = vec![wt("main", "main"), wt("feat-x", "feat/x")];
+
+        assert_eq!(
+            find_worktree(worktrees.clone(), "feat/x").unwrap().name,
+            "feat-x"
+        );
+        assert_eq!(
+            find_worktree(worktrees.clone(), "main").unwrap().name,
+            "main"
+        );
+        assert!(find_worktree(worktrees, "nope").is_none());
+    }
+}
@@ -1,6 +1,5 @@
 use crate::{cli, config::Shell};
 use indoc::formatdoc;
-use strum::IntoEnumIterator;
 
 pub fn detect_shell() -> Option<Shell> {
     let shell = std::env::var("SHELL").ok()?;
@@ -16,7 +15,14 @@ pub fn detect_shell() -> Option<Shell> {
 
 pub fn generate_hook(shell: Shell) -> String {
     let binary_name = env!("CARGO_BIN_NAME");
-    let mut commands: Vec<String> = cli::Command::iter().map(|c| c.to_string()).collect();
+    let mut commands = cli::Cli::subcommand_names();
+    // Completed along with project names and aliases from `bvo complete`.
+    let subcommands = commands
+        .iter()
+        .filter(|c| *c != "complete")
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(" ");
 
     commands.push("-v".to_string());
     commands.push("--version".to_string());
@@ -51,9 +57,17 @@ pub fn generate_hook(shell: Shell) -> String {
                         ;;
                 esac
             }}
+
+            function _{binary_name}() {{
+                if (( CURRENT == 2 )); then
+                    compadd -- {subcommands} $(command {binary_name} complete 2>/dev/null)
+                fi
+            }}
+            (( $+functions[compdef] )) && compdef _{binary_name} {binary_name}
         "#,
         binary_name = binary_name,
-        commands = commands
+        commands = commands,
+        subcommands = subcommands
         }
         .to_string(),
 
@@ -81,9 +95,18 @@ pub fn generate_hook(shell: Shell) -> String {
                         ;;
                 esac
             }}
+
+            function _{binary_name}_complete() {{
+                if [[ $COMP_CWORD -eq 1 ]]; then
+                    local words="{subcommands} $(command {binary_name} complete 2>/dev/null)"
+                    COMPREPLY=($(compgen -W "$words" -- "${{COMP_WORDS[1]}}"))
+                fi
+            }}
+            complete -F _{binary_name}_complete {binary_name}
         "#,
         binary_name = binary_name,
-        commands = commands
+        commands = commands,
+        subcommands = subcommands
         }
         .to_string(),
 
@@ -107,9 +130,13 @@ pub fn generate_hook(shell: Shell) -> String {
                         and cd $dir
                 end
             end
+
+            complete -c {binary_name} -f -n __fish_use_subcommand -a '{subcommands}'
+            complete -c {binary_name} -f -n __fish_use_subcommand -a '(command {binary_name} complete 2>/dev/null)'
         "#,
         binary_name = binary_name,
-        commands = commands.replace("|", " ")
+        commands = commands.replace("|", " "),
+        subcommands = subcommands
         }
         .to_string(),
     }
@@ -126,6 +153,8 @@ mod tests {
         assert!(hook.contains("command bvo pick"));
         assert!(hook.contains("command bvo pick \"$@\""));
         assert!(hook.contains("cd \"$dir\""));
+        assert!(hook.contains("compdef _bvo bvo"));
+        assert!(hook.contains("command bvo complete"));
     }
 
     #[test]
@@ -135,6 +164,7 @@ mod tests {
         assert!(hook.contains("command bvo pick"));
         assert!(hook.contains("command bvo pick \"$@\""));
         assert!(hook.contains("cd \"$dir\""));
+        assert!(hook.contains("complete -F _bvo_complete bvo"));
     }
 
     #[test]
@@ -144,6 +174,7 @@ mod tests {
         assert!(hook.contains("command bvo pick"));
         assert!(hook.contains("command bvo pick $argv"));
         assert!(hook.contains("cd $dir"));
+        assert!(hook.contains("(command bvo complete 2>/dev/null)"));
     }
 
     #[test]
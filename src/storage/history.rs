@@ -0,0 +1,230 @@
+use super::StorageError;
+use crate::project::Project;
+use crate::utils::fs::write_atomic;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Entries kept in the journal; older ones are dropped when it grows past this.
+const HISTORY_LIMIT: usize = 200;
+
+/// One registry mutation, along with the registry as it was before it ran.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HistoryEntry {
+    pub timestamp: DateTime<Utc>,
+    pub operation: String,
+    /// Number of earlier operations this entry reverted (`bvo undo`).
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub reverts: usize,
+    pub projects: Vec<Project>,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+/// What a transaction records in the journal.
+pub(super) struct Operation {
+    pub description: String,
+    pub reverts: usize,
+}
+
+impl Operation {
+    pub fn new(description: impl Into<String>) -> Self {
+        Self {
+            description: description.into(),
+            reverts: 0,
+        }
+    }
+
+    pub fn undo(count: usize) -> Self {
+        Self {
+            description: format!("undo {count}"),
+            reverts: count,
+        }
+    }
+}
+
+/// Append-only JSON Lines log of registry mutations, stored next to the
+/// registry as `projects.history.jsonl`.
+pub(super) struct Journal {
+    path: PathBuf,
+}
+
+impl Journal {
+    pub fn for_registry(registry: &Path) -> Self {
+        Self {
+            path: registry.with_extension("history.jsonl"),
+        }
+    }
+
+    /// Entries oldest first. Lines that fail to parse are skipped so a torn
+    /// write never makes the whole history unreadable.
+    pub fn read(&self) -> Result<Vec<HistoryEntry>, StorageError> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        Ok(fs::read_to_string(&self.path)?
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// Appends `entry`, dropping the oldest lines past [`HISTORY_LIMIT`].
+    /// Lines are counted and kept verbatim, never parsed.
+    pub fn append(&self, entry: &HistoryEntry) -> Result<(), StorageError> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        drop(file);
+
+        let content = fs::read_to_string(&self.path)?;
+        let lines = content.lines().count();
+        if lines > HISTORY_LIMIT {
+            let mut kept = String::with_capacity(content.len());
+            for line in content.lines().skip(lines - HISTORY_LIMIT) {
+                kept.push_str(line);
+                kept.push('\n');
+            }
+            write_atomic(&self.path, kept)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The registry `before` an operation as recorded in the journal. Visit logs
+/// change on every jump and are not journaled, so they are left out except for
+/// projects the operation removed, which have nowhere else to come back from.
+pub fn snapshot(before: &[Project], after: &[Project]) -> Vec<Project> {
+    before
+        .iter()
+        .map(|project| {
+            if !after.iter().any(|p| p.path == project.path) {
+                return project.clone();
+            }
+            Project {
+                visit_log: Default::default(),
+                worktree_visits: Default::default(),
+                ..project.clone()
+            }
+        })
+        .collect()
+}
+
+/// Gives each project of a restored snapshot the visits of its current
+/// version, so undo never rolls back visits, which are not journaled.
+pub fn keep_visits(restored: &mut [Project], current: &[Project]) {
+    for project in restored {
+        let Some(now) = current.iter().find(|p| p.path == project.path) else {
+            continue;
+        };
+        project.visits = now.visits;
+        project.last_opened_at = now.last_opened_at;
+        project.visit_log = now.visit_log.clone();
+        project.worktree_visits = now.worktree_visits.clone();
+    }
+}
+
+/// Entries that `bvo undo` can still revert, most recent first.
+///
+/// Undo entries are not undoable themselves; each one hides the operations it
+/// reverted, so repeated undos keep walking further back.
+pub fn undoable(entries: &[HistoryEntry]) -> Vec<&HistoryEntry> {
+    let mut skip = 0;
+    let mut result = Vec::new();
+
+    for entry in entries.iter().rev() {
+        if entry.reverts > 0 {
+            skip += entry.reverts;
+        } else if skip > 0 {
+            skip -= 1;
+        } else {
+            result.push(entry);
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(operation: &str, reverts: usize) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: Utc::now(),
+            operation: operation.to_string(),
+            reverts,
+            projects: Vec::new(),
+        }
+    }
+
+    fn operations(entries: &[HistoryEntry]) -> Vec<&str> {
+        undoable(entries)
+            .iter()
+            .map(|e| e.operation.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_undoable_skips_reverted() {
+        let entries = vec![
+            entry("a", 0),
+            entry("b", 0),
+            entry("c", 0),
+            entry("undo 2", 2),
+            entry("d", 0),
+        ];
+        assert_eq!(operations(&entries), vec!["d", "a"]);
+    }
+
+    #[test]
+    fn test_repeated_undo_walks_back() {
+        let entries = vec![
+            entry("a", 0),
+            entry("b", 0),
+            entry("undo 1", 1),
+            entry("undo 1", 1),
+        ];
+        assert!(operations(&entries).is_empty());
+    }
+
+    #[test]
+    fn test_append_trims_to_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = Journal::for_registry(&dir.path().join("projects.json"));
+
+        for i in 0..HISTORY_LIMIT + 5 {
+            journal.append(&entry(&format!("op {i}"), 0)).unwrap();
+        }
+
+        let entries = journal.read().unwrap();
+        assert_eq!(entries.len(), HISTORY_LIMIT);
+        assert_eq!(entries[0].operation, "op 5");
+    }
+
+    #[test]
+    fn test_snapshots_leave_out_visits() {
+        let mut kept = Project::new("kept".to_string(), "/tmp/kept".into(), false);
+        kept.on_access();
+        kept.on_worktree_access("main");
+        let mut removed = Project::new("removed".to_string(), "/tmp/removed".into(), false);
+        removed.on_access();
+
+        let before = vec![kept.clone(), removed.clone()];
+        let mut restored = snapshot(&before, std::slice::from_ref(&kept));
+        assert!(restored[0].visit_log.is_empty());
+        assert!(restored[0].worktree_visits.is_empty());
+        assert_eq!(restored[1], removed);
+
+        kept.on_access();
+        keep_visits(&mut restored, std::slice::from_ref(&kept));
+        assert_eq!(restored, vec![kept, removed]);
+    }
+}
@@ -0,0 +1,95 @@
+use super::StorageError;
+use serde_json::{json, Value};
+
+/// Schema version written by this build of bivio.
+pub const CURRENT_SCHEMA_VERSION: u32 = 2;
+
+type Migration = fn(Value) -> Result<Value, StorageError>;
+
+/// `MIGRATIONS[n]` upgrades a registry from schema version `n` to `n + 1`.
+const MIGRATIONS: [Migration; CURRENT_SCHEMA_VERSION as usize] = [v0_to_v1, v1_to_v2];
+
+/// Detects the schema version of a raw registry document.
+///
+/// Registries written before versioning was introduced are a bare array of
+/// projects and are reported as version 0.
+pub fn schema_version(value: &Value) -> Result<u32, StorageError> {
+    match value {
+        Value::Array(_) => Ok(0),
+        Value::Object(map) => map
+            .get("schema_version")
+            .and_then(Value::as_u64)
+            .map(|v| v as u32)
+            .ok_or_else(|| StorageError::InvalidRegistry("missing schema_version".to_string())),
+        _ => Err(StorageError::InvalidRegistry(
+            "expected an object or an array".to_string(),
+        )),
+    }
+}
+
+/// Upgrades `value` step by step until it reaches [`CURRENT_SCHEMA_VERSION`].
+pub fn migrate(mut value: Value) -> Result<Value, StorageError> {
+    let mut version = schema_version(&value)?;
+
+    if version > CURRENT_SCHEMA_VERSION {
+        return Err(StorageError::UnsupportedSchemaVersion(
+            version,
+            CURRENT_SCHEMA_VERSION,
+        ));
+    }
+
+    while version < CURRENT_SCHEMA_VERSION {
+        value = MIGRATIONS[version as usize](value)?;
+        version += 1;
+    }
+
+    Ok(value)
+}
+
+/// Wraps the legacy bare project array in a versioned envelope.
+fn v0_to_v1(value: Value) -> Result<Value, StorageError> {
+    Ok(json!({
+        "schema_version": 1,
+        "projects": value,
+    }))
+}
+
+/// Version 2 stores paths relative to `~` or a named root. Absolute paths are
+/// still valid, so only the version changes; paths are rewritten on save.
+fn v1_to_v2(mut value: Value) -> Result<Value, StorageError> {
+    value["schema_version"] = json!(2);
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_array_is_version_zero() {
+        assert_eq!(schema_version(&json!([])).unwrap(), 0);
+        assert_eq!(
+            schema_version(&json!({ "schema_version": 1, "projects": [] })).unwrap(),
+            1
+        );
+        assert!(schema_version(&json!("nope")).is_err());
+    }
+
+    #[test]
+    fn test_migrate_legacy_array() {
+        let legacy = json!([{ "name": "alpha", "path": "/tmp/alpha" }]);
+        let migrated = migrate(legacy).unwrap();
+
+        assert_eq!(migrated["schema_version"], CURRENT_SCHEMA_VERSION);
+        assert_eq!(migrated["projects"][0]["name"], "alpha");
+    }
+
+    #[test]
+    fn test_migrate_rejects_newer_schema() {
+        let future = json!({ "schema_version": CURRENT_SCHEMA_VERSION + 1, "projects": [] });
+        assert!(matches!(
+            migrate(future),
+            Err(StorageError::UnsupportedSchemaVersion(_, _))
+        ));
+    }
+}
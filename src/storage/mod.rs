@@ -0,0 +1,818 @@
+mod history;
+mod migrations;
+mod paths;
+
+pub use history::{undoable, HistoryEntry};
+pub use paths::PathMapper;
+
+use crate::config::Config;
+use crate::profile;
+use crate::project::Project;
+use crate::ranking::Ranking;
+use crate::sync::{SyncRepo, REGISTRY_FILE};
+use crate::tag_query::TagQuery;
+use crate::utils::fs::write_atomic;
+use chrono::Utc;
+use history::{Journal, Operation};
+use migrations::CURRENT_SCHEMA_VERSION;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+use std::result::Result;
+use std::{fs, io};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum StorageError {
+    #[error("project with name '{0}' already exists")]
+    DuplicateProjectName(String),
+    #[error("project with path '{0}' already exists")]
+    DuplicateProjectPath(String),
+    #[error("project '{0}' not found")]
+    ProjectNotFound(String),
+    #[error("'{0}' is already an alias of project '{1}'")]
+    AliasInUse(String, String),
+    #[error("registry schema version {0} is newer than the supported version {1}")]
+    UnsupportedSchemaVersion(u32, u32),
+    #[error("invalid registry: {0}")]
+    InvalidRegistry(String),
+    #[error("nothing to undo")]
+    NothingToUndo,
+
+    #[error(transparent)]
+    FromJson(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Sync(anyhow::Error),
+
+    #[error(transparent)]
+    IOError(#[from] io::Error),
+}
+
+/// On-disk envelope of `projects.json`.
+#[derive(Serialize)]
+struct Registry<'a> {
+    schema_version: u32,
+    projects: &'a [Project],
+}
+
+#[derive(Deserialize)]
+struct OwnedRegistry {
+    projects: Vec<Project>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Storage {
+    path: PathBuf,
+    projects: Vec<Project>,
+    paths: PathMapper,
+    /// Registry as loaded, kept when mutations are only applied in memory.
+    dry_run_original: Option<Vec<Project>>,
+}
+
+impl Storage {
+    /// Loads the registry of the active profile.
+    pub fn load() -> Result<Self, StorageError> {
+        Self::load_profile(profile::active())
+    }
+
+    pub fn load_profile(profile: &str) -> Result<Self, StorageError> {
+        // A broken config is reported by `bvo doctor`; fall back to the
+        // home directory alone rather than refusing to load the registry.
+        let config = Config::load().unwrap_or_default();
+        Self::open(profile::registry_path(profile), config.path_mapper())
+    }
+
+    /// Projects of every profile, each marked with the profile it came from.
+    pub fn load_all() -> Result<Vec<Project>, StorageError> {
+        let mut projects = Vec::new();
+        for name in profile::all()? {
+            let storage = Self::load_profile(&name)?;
+            projects.extend(storage.projects.into_iter().map(|p| Project {
+                profile: Some(name.clone()),
+                ..p
+            }));
+        }
+
+        Ok(projects)
+    }
+
+    /// Loads `path` with paths relative to the home directory only.
+    #[cfg(test)]
+    pub fn load_from(path: PathBuf) -> Result<Self, StorageError> {
+        Self::open(path, PathMapper::new(dirs::home_dir()))
+    }
+
+    pub fn open(path: PathBuf, paths: PathMapper) -> Result<Self, StorageError> {
+        let (projects, version) = Self::read(&path, &paths)?;
+        let mut storage = Self {
+            path,
+            projects,
+            paths,
+            dry_run_original: None,
+        };
+
+        // Persist the upgraded registry right away so the backup is taken once.
+        if version < CURRENT_SCHEMA_VERSION {
+            storage.transaction(None, |_| Ok(()))?;
+        }
+
+        Ok(storage)
+    }
+
+    /// Reads and migrates the registry, returning the projects along with the
+    /// schema version found on disk.
+    fn read(path: &Path, paths: &PathMapper) -> Result<(Vec<Project>, u32), StorageError> {
+        if !path.exists() {
+            return Ok((Vec::new(), CURRENT_SCHEMA_VERSION));
+        }
+
+        Self::parse(&fs::read_to_string(path)?, paths)
+    }
+
+    fn parse(content: &str, paths: &PathMapper) -> Result<(Vec<Project>, u32), StorageError> {
+        let value: serde_json::Value = serde_json::from_str(content)?;
+        let version = migrations::schema_version(&value)?;
+        let mut registry: OwnedRegistry = serde_json::from_value(migrations::migrate(value)?)?;
+        for project in &mut registry.projects {
+            project.path = paths.to_local(&project.path);
+        }
+
+        Ok((registry.projects, version))
+    }
+
+    /// Projects of a registry document written by any machine, with paths
+    /// translated for this one.
+    pub fn parse_registry(&self, content: &str) -> Result<Vec<Project>, StorageError> {
+        Ok(Self::parse(content, &self.paths)?.0)
+    }
+
+    /// The registry as it is written to disk.
+    pub fn registry_json(&self) -> Result<String, StorageError> {
+        self.serialize(&self.projects)
+    }
+
+    fn serialize(&self, projects: &[Project]) -> Result<String, StorageError> {
+        let projects: Vec<Project> = projects
+            .iter()
+            .map(|p| Project {
+                path: self.paths.to_portable(&p.path),
+                ..p.clone()
+            })
+            .collect();
+        let registry = Registry {
+            schema_version: CURRENT_SCHEMA_VERSION,
+            projects: &projects,
+        };
+        Ok(serde_json::to_string_pretty(&registry)?)
+    }
+
+    fn save(&self) -> Result<(), StorageError> {
+        write_atomic(&self.path, self.serialize(&self.projects)?)?;
+        Ok(())
+    }
+
+    /// When enabled, mutations are applied in memory only and never saved;
+    /// [`Storage::dry_run_diff`] shows what they would have changed.
+    pub fn dry_run(mut self, enabled: bool) -> Self {
+        self.dry_run_original = enabled.then(|| self.projects.clone());
+        self
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run_original.is_some()
+    }
+
+    /// Unified diff of the registry file between load and now, or `None` when
+    /// nothing changed or dry-run is off.
+    pub fn dry_run_diff(&self) -> Result<Option<String>, StorageError> {
+        let Some(original) = &self.dry_run_original else {
+            return Ok(None);
+        };
+
+        let before = self.serialize(original)?;
+        let after = self.serialize(&self.projects)?;
+        if before == after {
+            return Ok(None);
+        }
+
+        let file_name = self.path.display().to_string();
+        let diff = similar::TextDiff::from_lines(&before, &after)
+            .unified_diff()
+            .context_radius(3)
+            .header(&file_name, &file_name)
+            .to_string();
+
+        Ok(Some(diff))
+    }
+
+    /// Path of the copy kept before a registry at `version` is migrated.
+    fn backup_path(&self, version: u32) -> PathBuf {
+        self.path.with_extension(format!("json.v{version}.bak"))
+    }
+
+    /// Takes an exclusive advisory lock on `<registry>.lock`, released when the
+    /// returned file is dropped.
+    fn lock(&self) -> Result<fs::File, StorageError> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.path.with_extension("json.lock"))?;
+        file.lock()?;
+
+        Ok(file)
+    }
+
+    /// Runs a load-modify-save cycle under the registry lock. The registry is
+    /// re-read from disk first so changes made by concurrent invocations since
+    /// `load` are not overwritten.
+    ///
+    /// When `operation` is given and the registry changed, the previous state
+    /// is appended to the history journal so it can be undone, and the new one
+    /// is committed to the sync repository if there is one.
+    fn transaction<T, F>(&mut self, operation: Option<Operation>, f: F) -> Result<T, StorageError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, StorageError>,
+    {
+        if self.is_dry_run() {
+            return f(self);
+        }
+
+        let _lock = self.lock()?;
+        let (projects, version) = Self::read(&self.path, &self.paths)?;
+        self.projects = projects.clone();
+        let result = f(self)?;
+        if version < CURRENT_SCHEMA_VERSION {
+            fs::copy(&self.path, self.backup_path(version))?;
+        }
+        self.save()?;
+
+        if let Some(operation) = operation {
+            let content = self.serialize(&self.projects)?;
+            if self.serialize(&projects)? != content {
+                // Journaled first: the change is already saved, so a sync
+                // repository that fails to commit must not keep it from undo.
+                self.journal().append(&HistoryEntry {
+                    timestamp: Utc::now(),
+                    operation: operation.description.clone(),
+                    reverts: operation.reverts,
+                    projects: history::snapshot(&projects, &self.projects),
+                })?;
+                if let Some(sync) = SyncRepo::open(&self.path).map_err(StorageError::Sync)? {
+                    sync.commit_file(REGISTRY_FILE, &content, &operation.description)
+                        .map_err(StorageError::Sync)?;
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn journal(&self) -> Journal {
+        Journal::for_registry(&self.path)
+    }
+
+    /// Journaled operations, oldest first.
+    pub fn history(&self) -> Result<Vec<HistoryEntry>, StorageError> {
+        self.journal().read()
+    }
+
+    /// Restores the registry as it was before the last `count` operations and
+    /// returns those operations, most recent first.
+    pub fn undo(&mut self, count: usize) -> Result<Vec<HistoryEntry>, StorageError> {
+        let history = self.history()?;
+        let reverted: Vec<HistoryEntry> = history::undoable(&history)
+            .into_iter()
+            .take(count)
+            .cloned()
+            .collect();
+        let Some(mut restored) = reverted.last().map(|e| e.projects.clone()) else {
+            return Err(StorageError::NothingToUndo);
+        };
+
+        self.transaction(Some(Operation::undo(reverted.len())), |storage| {
+            history::keep_visits(&mut restored, &storage.projects);
+            storage.projects = restored;
+            Ok(())
+        })?;
+
+        Ok(reverted)
+    }
+
+    /// Registry file of the active profile.
+    pub fn path() -> PathBuf {
+        profile::registry_path(profile::active())
+    }
+
+    pub fn add(&mut self, project: Project) -> Result<(), StorageError> {
+        let operation = Operation::new(format!("add {}", project.name));
+        self.transaction(Some(operation), |storage| {
+            storage.check_available(&project.name, None)?;
+            for alias in &project.aliases {
+                storage.check_available(alias, None)?;
+            }
+            if storage.find_by_path(&project.path).is_some() {
+                return Err(StorageError::DuplicateProjectPath(
+                    project.path.to_string_lossy().to_string(),
+                ));
+            }
+            storage.projects.push(project);
+            Ok(())
+        })
+    }
+
+    pub fn remove_all(&mut self) -> Result<(), StorageError> {
+        self.transaction(Some(Operation::new("remove all")), |storage| {
+            storage.projects = vec![];
+            Ok(())
+        })
+    }
+
+    /// Removes every project matching `query`.
+    pub fn remove_all_filtered(&mut self, query: &TagQuery) -> Result<(), StorageError> {
+        let operation = Operation::new(format!("remove all -t '{query}'"));
+        self.transaction(Some(operation), |storage| {
+            storage.projects.retain(|project| !query.matches(project));
+            Ok(())
+        })
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<(), StorageError> {
+        let operation = Operation::new(format!("remove {name}"));
+        self.transaction(Some(operation), |storage| {
+            let len_before = storage.projects.len();
+            storage.projects.retain(|p| p.name != name);
+            if storage.projects.len() == len_before {
+                return Err(StorageError::ProjectNotFound(name.to_string()));
+            }
+            Ok(())
+        })
+    }
+
+    /// Replaces every project with what `f` returns for the current ones.
+    pub fn replace_with<F>(&mut self, description: &str, f: F) -> Result<(), StorageError>
+    where
+        F: FnOnce(&[Project]) -> Vec<Project>,
+    {
+        self.transaction(Some(Operation::new(description)), |storage| {
+            storage.projects = f(&storage.projects);
+            Ok(())
+        })
+    }
+
+    /// Renames a project, keeping its tags and frecency.
+    pub fn rename(&mut self, name: &str, new_name: &str) -> Result<(), StorageError> {
+        let operation = Operation::new(format!("rename {name} to {new_name}"));
+        self.transaction(Some(operation), |storage| {
+            storage.check_available(new_name, Some(name))?;
+            let project = storage
+                .find_by_name_mut(name)
+                .ok_or_else(|| StorageError::ProjectNotFound(name.to_string()))?;
+            project.name = new_name.to_string();
+            project.aliases.retain(|a| a != new_name);
+            Ok(())
+        })
+    }
+
+    /// Adds `alias` to a project. Aliases share one namespace with names.
+    pub fn add_alias(&mut self, name: &str, alias: &str) -> Result<(), StorageError> {
+        let operation = Operation::new(format!("alias {name} as {alias}"));
+        self.transaction(Some(operation), |storage| {
+            storage.check_available(alias, Some(name))?;
+            let project = storage
+                .find_by_name_mut(name)
+                .ok_or_else(|| StorageError::ProjectNotFound(name.to_string()))?;
+            if !project.aliases.iter().any(|a| a == alias) {
+                project.aliases.push(alias.to_string());
+            }
+            Ok(())
+        })
+    }
+
+    pub fn remove_alias(&mut self, name: &str, alias: &str) -> Result<(), StorageError> {
+        let operation = Operation::new(format!("remove alias {alias} of {name}"));
+        self.modify(Some(operation), name, |project| {
+            project.aliases.retain(|a| a != alias);
+        })
+    }
+
+    /// Fails when `name` is the name of any project or an alias of a project
+    /// other than `owner`.
+    pub fn check_available(&self, name: &str, owner: Option<&str>) -> Result<(), StorageError> {
+        for project in &self.projects {
+            if project.name == name {
+                return Err(StorageError::DuplicateProjectName(name.to_string()));
+            }
+            if Some(project.name.as_str()) != owner && project.aliases.iter().any(|a| a == name) {
+                return Err(StorageError::AliasInUse(
+                    name.to_string(),
+                    project.name.clone(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Points a project at a new path, keeping its tags and frecency.
+    pub fn relocate(
+        &mut self,
+        name: &str,
+        path: PathBuf,
+        is_bare_repo: bool,
+    ) -> Result<(), StorageError> {
+        let operation = Operation::new(format!("move {name} to {}", path.display()));
+        self.transaction(Some(operation), |storage| {
+            if storage.find_by_path(&path).is_some_and(|p| p.name != name) {
+                return Err(StorageError::DuplicateProjectPath(
+                    path.to_string_lossy().to_string(),
+                ));
+            }
+            let project = storage
+                .find_by_name_mut(name)
+                .ok_or_else(|| StorageError::ProjectNotFound(name.to_string()))?;
+            project.path = path;
+            project.is_bare_repo = is_bare_repo;
+            Ok(())
+        })
+    }
+
+    /// All projects, in registry order.
+    pub fn into_projects(self) -> Vec<Project> {
+        self.projects
+    }
+
+    /// All projects, highest frecency first.
+    pub fn list(&self) -> Vec<&Project> {
+        filter_ranked(&self.projects, None)
+    }
+
+    /// Projects matching `query` (all when `None`), highest frecency first.
+    pub fn list_filtered(&self, query: Option<&TagQuery>) -> Vec<&Project> {
+        match query {
+            None => self.list(),
+            Some(query) => filter_ranked(&self.projects, Some(query)),
+        }
+    }
+
+    /// Records a visit, through `worktree` for bare projects, aging every
+    /// visit count once their total exceeds the configured cap. Visits happen
+    /// on every jump and would drown the history, so they are not journaled.
+    pub fn update_access(
+        &mut self,
+        name: &str,
+        worktree: Option<&str>,
+    ) -> Result<(), StorageError> {
+        let ranking = Ranking::current();
+        self.transaction(None, |storage| {
+            let project = storage
+                .find_by_name_mut(name)
+                .ok_or_else(|| StorageError::ProjectNotFound(name.to_string()))?;
+            match worktree {
+                Some(worktree) => project.on_worktree_access(worktree),
+                None => project.on_access(),
+            }
+            ranking.age_visits(&mut storage.projects);
+            Ok(())
+        })
+    }
+
+    pub fn find_by_name(&self, name: &str) -> Option<&Project> {
+        self.projects.iter().find(|p| p.name == name)
+    }
+
+    pub fn find_by_name_mut(&mut self, name: &str) -> Option<&mut Project> {
+        self.projects.iter_mut().find(|p| p.name == name)
+    }
+
+    pub fn find_by_path(&self, path: &PathBuf) -> Option<&Project> {
+        self.projects.iter().find(|p| p.path == *path)
+    }
+
+    pub fn update<F>(&mut self, name: &str, f: F) -> Result<(), StorageError>
+    where
+        F: FnOnce(&mut Project),
+    {
+        self.modify(Some(Operation::new(format!("update {name}"))), name, f)
+    }
+
+    fn modify<F>(
+        &mut self,
+        operation: Option<Operation>,
+        name: &str,
+        f: F,
+    ) -> Result<(), StorageError>
+    where
+        F: FnOnce(&mut Project),
+    {
+        self.transaction(operation, |storage| {
+            let project = storage
+                .find_by_name_mut(name)
+                .ok_or_else(|| StorageError::ProjectNotFound(name.to_string()))?;
+            f(project);
+            Ok(())
+        })
+    }
+}
+
+/// Like [`Storage::list_filtered`], for projects gathered from several
+/// registries with [`Storage::load_all`].
+pub fn filter_ranked<'a>(projects: &'a [Project], query: Option<&TagQuery>) -> Vec<&'a Project> {
+    let matching: Vec<&Project> = projects
+        .iter()
+        .filter(|p| query.is_none_or(|q| q.matches(p)))
+        .collect();
+
+    Ranking::current().ranked(matching, Utc::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn project(name: &str) -> Project {
+        Project::new(name.to_string(), PathBuf::from("/tmp").join(name), false)
+    }
+
+    #[test]
+    fn test_add_and_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("projects.json");
+
+        let mut storage = Storage::load_from(path.clone()).unwrap();
+        storage.add(project("alpha")).unwrap();
+
+        let reloaded = Storage::load_from(path).unwrap();
+        assert!(reloaded.find_by_name("alpha").is_some());
+    }
+
+    #[test]
+    fn test_stale_instance_does_not_clobber() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("projects.json");
+
+        let mut first = Storage::load_from(path.clone()).unwrap();
+        let mut second = Storage::load_from(path.clone()).unwrap();
+        first.add(project("alpha")).unwrap();
+        second.add(project("beta")).unwrap();
+
+        let reloaded = Storage::load_from(path).unwrap();
+        assert_eq!(reloaded.list().len(), 2);
+    }
+
+    #[test]
+    fn test_concurrent_writers_serialize() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("projects.json");
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let path = path.clone();
+                thread::spawn(move || {
+                    let mut storage = Storage::load_from(path).unwrap();
+                    storage.add(project(&format!("p{i}"))).unwrap();
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let reloaded = Storage::load_from(path).unwrap();
+        assert_eq!(reloaded.list().len(), 8);
+    }
+
+    #[test]
+    fn test_list_is_ordered_by_frecency() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut storage = Storage::load_from(dir.path().join("projects.json")).unwrap();
+
+        for (name, visits) in [("low", 1), ("high", 50), ("mid", 10), ("none", 0)] {
+            let mut p = project(name);
+            p.visits = visits;
+            storage.add(p).unwrap();
+        }
+
+        let names: Vec<&str> = storage.list().iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["high", "mid", "low", "none"]);
+    }
+
+    #[test]
+    fn test_remove_all_filtered_removes_matches() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut storage = Storage::load_from(dir.path().join("projects.json")).unwrap();
+
+        storage
+            .add(project("old").with_tags(vec!["archived".to_string()]))
+            .unwrap();
+        storage.add(project("new")).unwrap();
+
+        storage
+            .remove_all_filtered(&"archived".parse().unwrap())
+            .unwrap();
+
+        assert!(storage.find_by_name("old").is_none());
+        assert!(storage.find_by_name("new").is_some());
+    }
+
+    #[test]
+    fn test_aliases_share_namespace_with_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut storage = Storage::load_from(dir.path().join("projects.json")).unwrap();
+        storage.add(project("frontend")).unwrap();
+        storage.add(project("backend")).unwrap();
+
+        storage.add_alias("frontend", "fe").unwrap();
+        assert!(matches!(
+            storage.add_alias("backend", "fe"),
+            Err(StorageError::AliasInUse(_, owner)) if owner == "frontend"
+        ));
+        assert!(matches!(
+            storage.add_alias("backend", "frontend"),
+            Err(StorageError::DuplicateProjectName(_))
+        ));
+        assert!(matches!(
+            storage.add(project("fe")),
+            Err(StorageError::AliasInUse(..))
+        ));
+        assert!(matches!(
+            storage.rename("backend", "fe"),
+            Err(StorageError::AliasInUse(..))
+        ));
+
+        // Renaming to its own alias drops the alias.
+        storage.rename("frontend", "fe").unwrap();
+        assert!(storage.find_by_name("fe").unwrap().aliases.is_empty());
+
+        storage.add_alias("backend", "be").unwrap();
+        storage.remove_alias("backend", "be").unwrap();
+        assert!(storage.find_by_name("backend").unwrap().aliases.is_empty());
+    }
+
+    #[test]
+    fn test_rename_and_relocate_keep_frecency() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut storage = Storage::load_from(dir.path().join("projects.json")).unwrap();
+
+        let mut alpha = project("alpha").with_tags(vec!["rust".to_string()]);
+        alpha.visits = 7;
+        storage.add(alpha).unwrap();
+        storage.add(project("beta")).unwrap();
+
+        assert!(matches!(
+            storage.rename("alpha", "beta"),
+            Err(StorageError::DuplicateProjectName(_))
+        ));
+        storage.rename("alpha", "gamma").unwrap();
+
+        assert!(matches!(
+            storage.relocate("gamma", PathBuf::from("/tmp/beta"), false),
+            Err(StorageError::DuplicateProjectPath(_))
+        ));
+        storage
+            .relocate("gamma", PathBuf::from("/tmp/elsewhere"), true)
+            .unwrap();
+
+        let gamma = storage.find_by_name("gamma").unwrap();
+        assert_eq!(gamma.visits, 7);
+        assert_eq!(gamma.tags, vec!["rust"]);
+        assert_eq!(gamma.path, PathBuf::from("/tmp/elsewhere"));
+        assert!(gamma.is_bare_repo);
+    }
+
+    #[test]
+    fn test_dry_run_does_not_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("projects.json");
+        Storage::load_from(path.clone())
+            .unwrap()
+            .add(project("alpha"))
+            .unwrap();
+
+        let mut storage = Storage::load_from(path.clone()).unwrap().dry_run(true);
+        assert!(storage.dry_run_diff().unwrap().is_none());
+
+        storage.remove("alpha").unwrap();
+        assert!(storage.find_by_name("alpha").is_none());
+
+        let diff = storage.dry_run_diff().unwrap().unwrap();
+        assert!(diff.contains("-      \"name\": \"alpha\""));
+
+        let reloaded = Storage::load_from(path).unwrap();
+        assert!(reloaded.find_by_name("alpha").is_some());
+    }
+
+    #[test]
+    fn test_undo_restores_previous_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut storage = Storage::load_from(dir.path().join("projects.json")).unwrap();
+
+        storage.add(project("alpha")).unwrap();
+        storage.add(project("beta")).unwrap();
+        storage.update_access("alpha", None).unwrap();
+        storage.remove_all().unwrap();
+        assert_eq!(storage.history().unwrap().len(), 3);
+
+        let reverted = storage.undo(1).unwrap();
+        assert_eq!(reverted[0].operation, "remove all");
+        assert_eq!(storage.find_by_name("alpha").unwrap().visits, 1);
+        assert_eq!(storage.find_by_name("alpha").unwrap().visit_log.total(), 1);
+        assert!(storage.find_by_name("beta").is_some());
+
+        storage.undo(1).unwrap();
+        assert!(storage.find_by_name("beta").is_none());
+        assert!(storage.find_by_name("alpha").is_some());
+
+        storage.undo(5).unwrap();
+        assert!(storage.list().is_empty());
+        assert!(matches!(storage.undo(1), Err(StorageError::NothingToUndo)));
+    }
+
+    #[test]
+    fn test_failed_sync_commit_is_journaled() {
+        let dir = tempfile::tempdir().unwrap();
+        // A sync directory whose repository cannot be opened.
+        fs::create_dir_all(dir.path().join("sync/.git")).unwrap();
+        let mut storage = Storage::load_from(dir.path().join("projects.json")).unwrap();
+
+        assert!(matches!(
+            storage.add(project("alpha")),
+            Err(StorageError::Sync(_))
+        ));
+        assert_eq!(storage.history().unwrap().len(), 1);
+
+        // The sync repository still fails, but the change is reverted.
+        assert!(storage.undo(1).is_err());
+        assert!(Storage::load_from(dir.path().join("projects.json"))
+            .unwrap()
+            .list()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_undo_zero_keeps_registry() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut storage = Storage::load_from(dir.path().join("projects.json")).unwrap();
+        storage.add(project("alpha")).unwrap();
+
+        assert!(matches!(storage.undo(0), Err(StorageError::NothingToUndo)));
+        assert!(storage.find_by_name("alpha").is_some());
+        assert_eq!(storage.history().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_dry_run_is_not_journaled() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("projects.json");
+        let mut storage = Storage::load_from(path).unwrap().dry_run(true);
+
+        storage.add(project("alpha")).unwrap();
+        assert!(storage.history().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_paths_are_stored_relative_to_home() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("projects.json");
+        let home = dir.path().join("home");
+        let paths = PathMapper::new(Some(home.clone()));
+
+        let mut storage = Storage::open(path.clone(), paths).unwrap();
+        let project = Project::new("alpha".to_string(), home.join("code/alpha"), false);
+        storage.add(project).unwrap();
+
+        let raw: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(raw["projects"][0]["path"], "~/code/alpha");
+
+        let elsewhere = PathMapper::new(Some(PathBuf::from("/home/other")));
+        let reloaded = Storage::open(path, elsewhere).unwrap();
+        assert_eq!(
+            reloaded.find_by_name("alpha").unwrap().path,
+            PathBuf::from("/home/other/code/alpha")
+        );
+    }
+
+    #[test]
+    fn test_legacy_registry_is_migrated_with_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("projects.json");
+        let legacy = r#"[{ "name": "alpha", "path": "/tmp/alpha" }]"#;
+        fs::write(&path, legacy).unwrap();
+
+        let storage = Storage::load_from(path.clone()).unwrap();
+        assert!(storage.find_by_name("alpha").is_some());
+
+        let backup = fs::read_to_string(dir.path().join("projects.json.v0.bak")).unwrap();
+        assert_eq!(backup, legacy);
+
+        let migrated: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(migrated["schema_version"], CURRENT_SCHEMA_VERSION);
+    }
+}
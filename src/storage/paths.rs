@@ -0,0 +1,159 @@
+use std::path::{Component, Path, PathBuf};
+
+/// Translates project paths between their on-disk form and this machine.
+///
+/// Paths under a named root (`path_roots` in config) are stored as
+/// `$name/rest`, paths under the home directory as `~/rest`, so one registry
+/// works on machines with different usernames or layouts. Absolute paths
+/// written elsewhere can be rewritten with `path_remap` prefixes.
+#[derive(Debug, Clone, Default)]
+pub struct PathMapper {
+    home: Option<PathBuf>,
+    roots: Vec<(String, PathBuf)>,
+    remap: Vec<(PathBuf, PathBuf)>,
+}
+
+impl PathMapper {
+    pub fn new(home: Option<PathBuf>) -> Self {
+        Self {
+            home,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_roots(mut self, roots: impl IntoIterator<Item = (String, PathBuf)>) -> Self {
+        self.roots = roots.into_iter().collect();
+        self
+    }
+
+    pub fn with_remap(mut self, remap: impl IntoIterator<Item = (PathBuf, PathBuf)>) -> Self {
+        self.remap = remap.into_iter().collect();
+        // Longest prefix first, so nested entries win.
+        self.remap
+            .sort_by_key(|(from, _)| std::cmp::Reverse(from.components().count()));
+        self
+    }
+
+    /// Form written to the registry: relative to the most specific root or
+    /// home directory containing `path`, otherwise unchanged.
+    pub fn to_portable(&self, path: &Path) -> PathBuf {
+        let home = self.home.iter().map(|home| ("~".to_string(), home));
+        let roots = self
+            .roots
+            .iter()
+            .map(|(name, root)| (format!("${name}"), root));
+
+        let best = roots
+            .chain(home)
+            .filter_map(|(prefix, base)| {
+                let rest = path.strip_prefix(base).ok()?;
+                Some((base.components().count(), prefix, rest))
+            })
+            .max_by_key(|(depth, _, _)| *depth);
+
+        match best {
+            Some((_, prefix, rest)) => Path::new(&prefix).join(rest),
+            None => path.to_path_buf(),
+        }
+    }
+
+    /// Path on this machine for a registry entry. Unknown roots are left as
+    /// they are, so the project shows up as missing instead of being dropped.
+    pub fn to_local(&self, path: &Path) -> PathBuf {
+        let mut components = path.components();
+        let first = match components.next() {
+            Some(Component::Normal(first)) => first.to_string_lossy(),
+            _ => return self.remapped(path),
+        };
+        let rest = components.as_path();
+
+        let base = if first == "~" {
+            self.home.as_ref()
+        } else if let Some(name) = first.strip_prefix('$') {
+            self.roots
+                .iter()
+                .find(|(root, _)| root == name)
+                .map(|(_, base)| base)
+        } else {
+            None
+        };
+
+        match base {
+            Some(base) => base.join(rest),
+            None => path.to_path_buf(),
+        }
+    }
+
+    fn remapped(&self, path: &Path) -> PathBuf {
+        self.remap
+            .iter()
+            .find_map(|(from, to)| Some(to.join(path.strip_prefix(from).ok()?)))
+            .unwrap_or_else(|| path.to_path_buf())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapper() -> PathMapper {
+        PathMapper::new(Some(PathBuf::from("/home/me")))
+            .with_roots([("work".to_string(), PathBuf::from("/home/me/work"))])
+            .with_remap([
+                (PathBuf::from("/Users/me"), PathBuf::from("/home/me")),
+                (
+                    PathBuf::from("/Users/me/src"),
+                    PathBuf::from("/home/me/code"),
+                ),
+            ])
+    }
+
+    #[test]
+    fn test_to_portable() {
+        let m = mapper();
+        assert_eq!(
+            m.to_portable(Path::new("/home/me/code/api")),
+            PathBuf::from("~/code/api")
+        );
+        assert_eq!(
+            m.to_portable(Path::new("/home/me/work/api")),
+            PathBuf::from("$work/api")
+        );
+        assert_eq!(m.to_portable(Path::new("/home/me")), PathBuf::from("~"));
+        assert_eq!(
+            m.to_portable(Path::new("/srv/api")),
+            PathBuf::from("/srv/api")
+        );
+    }
+
+    #[test]
+    fn test_to_local() {
+        let m = mapper();
+        assert_eq!(
+            m.to_local(Path::new("~/code/api")),
+            PathBuf::from("/home/me/code/api")
+        );
+        assert_eq!(
+            m.to_local(Path::new("$work/api")),
+            PathBuf::from("/home/me/work/api")
+        );
+        assert_eq!(
+            m.to_local(Path::new("$other/api")),
+            PathBuf::from("$other/api")
+        );
+        assert_eq!(m.to_local(Path::new("/srv/api")), PathBuf::from("/srv/api"));
+    }
+
+    #[test]
+    fn test_remap_prefers_longest_prefix() {
+        let m = mapper();
+        assert_eq!(
+            m.to_local(Path::new("/Users/me/src/api")),
+            PathBuf::from("/home/me/code/api")
+        );
+        assert_eq!(
+            m.to_local(Path::new("/Users/me/notes")),
+            PathBuf::from("/home/me/notes")
+        );
+    }
+}
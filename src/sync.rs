@@ -0,0 +1,480 @@
+use crate::Project;
+use anyhow::{bail, Result};
+use git2::{
+    Commit, Cred, CredentialType, FetchOptions, IndexAddOption, Oid, PushOptions, RemoteCallbacks,
+    Repository, Signature,
+};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File name of the registry inside the sync repository.
+pub const REGISTRY_FILE: &str = "projects.json";
+/// File name of the config inside the sync repository.
+pub const CONFIG_FILE: &str = "config.json";
+
+/// Branch of the default profile; named profiles sync on a branch each.
+pub const DEFAULT_BRANCH: &str = "main";
+
+const REMOTE: &str = "origin";
+
+/// Git repository next to the registry (`<config dir>/sync`) holding a copy of
+/// `projects.json`, and optionally `config.json`, committed on every change.
+pub struct SyncRepo {
+    repo: Repository,
+    dir: PathBuf,
+    branch: String,
+}
+
+impl SyncRepo {
+    fn dir_for(registry: &Path) -> PathBuf {
+        registry.with_file_name("sync")
+    }
+
+    /// Opens the sync repository of `registry`, or `None` if sync was never
+    /// set up.
+    pub fn open(registry: &Path) -> Result<Option<Self>> {
+        let dir = Self::dir_for(registry);
+        if !dir.join(".git").exists() {
+            return Ok(None);
+        }
+
+        let repo = Repository::open(&dir)?;
+        let branch = repo
+            .find_reference("HEAD")?
+            .symbolic_target()
+            .and_then(|r| r.strip_prefix("refs/heads/"))
+            .unwrap_or(DEFAULT_BRANCH)
+            .to_string();
+
+        Ok(Some(Self { repo, dir, branch }))
+    }
+
+    /// Opens or creates the sync repository of `registry`, pointing `origin`
+    /// at `remote` and syncing on `branch`.
+    pub fn init(registry: &Path, remote: &str, branch: &str) -> Result<Self> {
+        let sync = match Self::open(registry)? {
+            Some(sync) if sync.branch == branch => sync,
+            _ => {
+                let dir = Self::dir_for(registry);
+                let repo = Repository::init(&dir)?;
+                repo.set_head(&format!("refs/heads/{branch}"))?;
+                Self {
+                    repo,
+                    dir,
+                    branch: branch.to_string(),
+                }
+            }
+        };
+
+        match sync.repo.find_remote(REMOTE) {
+            Ok(existing) if existing.url() == Some(remote) => {}
+            Ok(_) => sync.repo.remote_set_url(REMOTE, remote)?,
+            Err(_) => {
+                sync.repo.remote(REMOTE, remote)?;
+            }
+        }
+
+        Ok(sync)
+    }
+
+    fn signature(&self) -> Result<Signature<'static>> {
+        Ok(self
+            .repo
+            .signature()
+            .or_else(|_| Signature::now(env!("CARGO_BIN_NAME"), "bvo@localhost"))?)
+    }
+
+    fn head(&self) -> Option<Commit<'_>> {
+        self.repo.head().ok()?.peel_to_commit().ok()
+    }
+
+    /// Writes `content` to `file` and commits it when it changed. Returns
+    /// whether a commit was made.
+    pub fn commit_file(&self, file: &str, content: &str, message: &str) -> Result<bool> {
+        self.commit(&[(file, content)], message, &[])
+    }
+
+    fn commit(&self, files: &[(&str, &str)], message: &str, merged: &[Oid]) -> Result<bool> {
+        let mut index = self.repo.index()?;
+        for (file, content) in files {
+            fs::write(self.dir.join(file), content)?;
+            index.add_all([file], IndexAddOption::DEFAULT, None)?;
+        }
+        index.write()?;
+        let tree = self.repo.find_tree(index.write_tree()?)?;
+
+        let head = self.head();
+        if merged.is_empty() && head.as_ref().is_some_and(|h| h.tree_id() == tree.id()) {
+            return Ok(false);
+        }
+
+        let mut parents: Vec<Commit> = head.into_iter().collect();
+        for oid in merged {
+            parents.push(self.repo.find_commit(*oid)?);
+        }
+        let parents: Vec<&Commit> = parents.iter().collect();
+
+        let signature = self.signature()?;
+        self.repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )?;
+
+        Ok(true)
+    }
+
+    /// Content of `file` at `commit`, if it exists there.
+    pub fn read_file(&self, commit: Oid, file: &str) -> Result<Option<String>> {
+        let tree = self.repo.find_commit(commit)?.tree()?;
+        let Ok(entry) = tree.get_path(Path::new(file)) else {
+            return Ok(None);
+        };
+        let blob = self.repo.find_blob(entry.id())?;
+
+        Ok(Some(String::from_utf8_lossy(blob.content()).into_owned()))
+    }
+
+    /// Fetches the remote branch, returning its commit unless the remote is
+    /// still empty.
+    pub fn fetch(&self) -> Result<Option<Oid>> {
+        let mut options = FetchOptions::new();
+        options.remote_callbacks(callbacks());
+        self.repo
+            .find_remote(REMOTE)?
+            .fetch(&[self.branch.as_str()], Some(&mut options), None)?;
+
+        Ok(self
+            .repo
+            .find_reference(&format!("refs/remotes/{REMOTE}/{}", self.branch))
+            .ok()
+            .and_then(|r| r.target()))
+    }
+
+    /// What joining local history with the fetched `theirs` requires.
+    pub fn plan(&self, theirs: Oid) -> Result<MergePlan> {
+        let Some(head) = self.head() else {
+            return Ok(MergePlan::Merge { base: None });
+        };
+        if head.id() == theirs || self.repo.graph_descendant_of(head.id(), theirs)? {
+            return Ok(MergePlan::UpToDate);
+        }
+
+        Ok(MergePlan::Merge {
+            base: self.repo.merge_base(head.id(), theirs).ok(),
+        })
+    }
+
+    /// Records the merge of `theirs` with `files` as the result. Files only
+    /// known to `theirs` are kept as they are there.
+    pub fn commit_merge(&self, files: &[(&str, &str)], theirs: Oid) -> Result<()> {
+        let mut all: Vec<(String, String)> = Vec::new();
+        for entry in self.repo.find_commit(theirs)?.tree()?.iter() {
+            let Some(name) = entry.name() else { continue };
+            if files.iter().any(|(file, _)| *file == name) {
+                continue;
+            }
+            if let Some(content) = self.read_file(theirs, name)? {
+                all.push((name.to_string(), content));
+            }
+        }
+
+        let mut files = files.to_vec();
+        files.extend(all.iter().map(|(f, c)| (f.as_str(), c.as_str())));
+        self.commit(&files, "Merge remote changes", &[theirs])?;
+
+        Ok(())
+    }
+
+    pub fn push(&self) -> Result<()> {
+        if self.head().is_none() {
+            return Ok(());
+        }
+
+        let rejection = RefCell::new(None);
+        {
+            let mut callbacks = callbacks();
+            callbacks.push_update_reference(|_, status| {
+                *rejection.borrow_mut() = status.map(String::from);
+                Ok(())
+            });
+            let mut options = PushOptions::new();
+            options.remote_callbacks(callbacks);
+
+            let refspec = format!("refs/heads/{0}:refs/heads/{0}", self.branch);
+            self.repo
+                .find_remote(REMOTE)?
+                .push(&[refspec.as_str()], Some(&mut options))?;
+        }
+
+        if let Some(reason) = rejection.into_inner() {
+            bail!("push rejected: {reason}");
+        }
+
+        Ok(())
+    }
+}
+
+pub enum MergePlan {
+    UpToDate,
+    /// `base` is `None` when the histories are unrelated, e.g. the first sync
+    /// of a machine that already had projects.
+    Merge {
+        base: Option<Oid>,
+    },
+}
+
+/// Uses the SSH agent for SSH remotes and git credential helpers for HTTPS.
+fn callbacks<'a>() -> RemoteCallbacks<'a> {
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(|url, username, allowed| {
+        if allowed.contains(CredentialType::SSH_KEY) {
+            return Cred::ssh_key_from_agent(username.unwrap_or("git"));
+        }
+        if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            if let Ok(config) = git2::Config::open_default() {
+                return Cred::credential_helper(&config, url, username);
+            }
+        }
+        Cred::default()
+    });
+    callbacks
+}
+
+/// Three-way merge of registries, keyed by project name.
+///
+/// Projects on both sides are merged field by field: tags, aliases and visit
+/// logs are united, the higher `visits` and the later `last_opened_at` win,
+/// and the path, description, notes, pinned and archived states follow
+/// whichever side changed them. A project removed on one side stays removed unless the other
+/// side changed it since `base`.
+pub fn merge(base: Option<&[Project]>, ours: &[Project], theirs: &[Project]) -> Vec<Project> {
+    let by_name = |projects: &[Project]| -> BTreeMap<String, Project> {
+        projects
+            .iter()
+            .map(|p| (p.name.clone(), p.clone()))
+            .collect()
+    };
+    let base = by_name(base.unwrap_or_default());
+    let mut theirs = by_name(theirs);
+    let mut merged = Vec::new();
+
+    for ours in ours {
+        let base = base.get(&ours.name);
+        match theirs.remove(&ours.name) {
+            Some(theirs) => merged.push(merge_project(base, ours, &theirs)),
+            // Removed by them; keep only if we touched it since.
+            None if base.is_some_and(|b| b == ours) => {}
+            None => merged.push(ours.clone()),
+        }
+    }
+
+    for (name, theirs) in theirs {
+        // Removed by us; keep only if they touched it since.
+        if base.get(&name).is_none_or(|b| *b != theirs) {
+            merged.push(theirs);
+        }
+    }
+
+    // Two machines may have registered the same directory under different
+    // names; keep the first.
+    let mut seen = Vec::new();
+    merged.retain(|p| {
+        let duplicate = seen.contains(&p.path);
+        seen.push(p.path.clone());
+        !duplicate
+    });
+
+    merged
+}
+
+fn merge_project(base: Option<&Project>, ours: &Project, theirs: &Project) -> Project {
+    let mut merged = ours.clone();
+
+    for tag in &theirs.tags {
+        merged.add_tag(tag.clone());
+    }
+    for alias in &theirs.aliases {
+        if !merged.aliases.contains(alias) {
+            merged.aliases.push(alias.clone());
+        }
+    }
+    merged.visit_log = ours.visit_log.merge(&theirs.visit_log);
+    for (worktree, log) in &theirs.worktree_visits {
+        let merged_log = merged.worktree_visits.entry(worktree.clone()).or_default();
+        *merged_log = merged_log.merge(log);
+    }
+    // Visits logged on both machines add up beyond either counter.
+    let logged = u32::try_from(merged.visit_log.total()).unwrap_or(u32::MAX);
+    merged.visits = ours.visits.max(theirs.visits).max(logged);
+    merged.last_opened_at = ours.last_opened_at.max(theirs.last_opened_at);
+    merged.added_at = ours.added_at.min(theirs.added_at);
+
+    let ours_moved = base.is_some_and(|b| b.path != ours.path);
+    if !ours_moved && ours.path != theirs.path {
+        merged.path = theirs.path.clone();
+        merged.is_bare_repo = theirs.is_bare_repo;
+    }
+    if base.is_some_and(|b| b.description == ours.description) {
+        merged.description = theirs.description.clone();
+    }
+    if base.is_some_and(|b| b.notes == ours.notes) {
+        merged.notes = theirs.notes.clone();
+    }
+    if base.is_some_and(|b| b.pinned == ours.pinned) {
+        merged.pinned = theirs.pinned;
+    }
+    if base.is_some_and(|b| b.archived == ours.archived) {
+        merged.archived = theirs.archived;
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    fn project(name: &str) -> Project {
+        Project::new(name.to_string(), PathBuf::from("/tmp").join(name), false)
+    }
+
+    fn names(projects: &[Project]) -> Vec<&str> {
+        projects.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_merge_fields() {
+        let base = project("api");
+        let mut ours = base.clone().with_tags(vec!["work".to_string()]);
+        ours.visits = 3;
+        ours.last_opened_at = base.last_opened_at + Duration::hours(1);
+        let mut theirs = base.clone().with_tags(vec!["rust".to_string()]);
+        theirs.visits = 9;
+        theirs.path = PathBuf::from("/srv/api");
+        ours.notes = Some("deploy on fridays".to_string());
+        theirs.description = Some("Billing API".to_string());
+
+        let merged = merge(Some(&[base]), &[ours.clone()], &[theirs]);
+
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].tags, vec!["work", "rust"]);
+        assert_eq!(merged[0].visits, 9);
+        assert_eq!(merged[0].last_opened_at, ours.last_opened_at);
+        assert_eq!(merged[0].path, PathBuf::from("/srv/api"));
+        assert_eq!(merged[0].notes, ours.notes);
+        assert_eq!(merged[0].description.as_deref(), Some("Billing API"));
+    }
+
+    #[test]
+    fn test_merge_visit_logs() {
+        let mut base = project("api");
+        base.visits = 1;
+        base.visit_log.record(base.added_at - Duration::days(1));
+        let mut ours = base.clone();
+        ours.on_worktree_access("main");
+        let mut theirs = base.clone();
+        theirs.last_opened_at += Duration::hours(1);
+        theirs.visits += 1;
+        theirs.visit_log.record(theirs.last_opened_at);
+
+        let merged = merge(Some(&[base]), &[ours], &[theirs]);
+
+        assert_eq!(merged[0].visit_log.total(), 3);
+        assert_eq!(merged[0].visits, 3);
+        assert_eq!(merged[0].worktree_visits["main"].total(), 1);
+    }
+
+    #[test]
+    fn test_merge_additions_and_removals() {
+        let base = [project("kept"), project("gone-there"), project("gone-here")];
+        let ours = [base[0].clone(), base[1].clone(), project("new-here")];
+        let theirs = [base[0].clone(), base[2].clone(), project("new-there")];
+
+        let merged = merge(Some(&base), &ours, &theirs);
+        assert_eq!(names(&merged), vec!["kept", "new-here", "new-there"]);
+    }
+
+    #[test]
+    fn test_merge_keeps_removed_project_changed_elsewhere() {
+        let base = project("api");
+        let mut theirs = base.clone();
+        theirs.visits = 4;
+
+        let merged = merge(Some(&[base]), &[], &[theirs]);
+        assert_eq!(names(&merged), vec!["api"]);
+    }
+
+    #[test]
+    fn test_merge_without_base_unites() {
+        let mut ours = project("api");
+        ours.last_opened_at = Utc::now();
+        let merged = merge(
+            None,
+            &[ours, project("web")],
+            &[project("api"), project("cli")],
+        );
+        assert_eq!(names(&merged), vec!["api", "web", "cli"]);
+    }
+
+    #[test]
+    fn test_sync_through_bare_remote() {
+        let dir = tempfile::tempdir().unwrap();
+        let remote = dir.path().join("remote.git");
+        Repository::init_bare(&remote).unwrap();
+        let remote = remote.to_str().unwrap();
+
+        let laptop = SyncRepo::init(
+            &dir.path().join("laptop/projects.json"),
+            remote,
+            DEFAULT_BRANCH,
+        )
+        .unwrap();
+        assert!(laptop.commit_file(REGISTRY_FILE, "one", "add one").unwrap());
+        assert!(!laptop.commit_file(REGISTRY_FILE, "one", "noop").unwrap());
+        laptop.push().unwrap();
+
+        let desk = SyncRepo::init(
+            &dir.path().join("desk/projects.json"),
+            remote,
+            DEFAULT_BRANCH,
+        )
+        .unwrap();
+        let theirs = desk.fetch().unwrap().unwrap();
+        assert!(matches!(
+            desk.plan(theirs).unwrap(),
+            MergePlan::Merge { base: None }
+        ));
+        assert_eq!(
+            desk.read_file(theirs, REGISTRY_FILE).unwrap().as_deref(),
+            Some("one")
+        );
+
+        desk.commit_merge(&[(CONFIG_FILE, "{}")], theirs).unwrap();
+        assert_eq!(
+            desk.read_file(desk.head().unwrap().id(), REGISTRY_FILE)
+                .unwrap()
+                .as_deref(),
+            Some("one")
+        );
+        desk.commit_file(REGISTRY_FILE, "one+two", "add two")
+            .unwrap();
+        desk.push().unwrap();
+
+        let theirs = laptop.fetch().unwrap().unwrap();
+        let MergePlan::Merge { base } = laptop.plan(theirs).unwrap() else {
+            panic!("expected a merge");
+        };
+        assert!(base.is_some());
+        laptop
+            .commit_merge(&[(REGISTRY_FILE, "one+two")], theirs)
+            .unwrap();
+        assert!(matches!(laptop.plan(theirs).unwrap(), MergePlan::UpToDate));
+    }
+}
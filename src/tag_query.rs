@@ -0,0 +1,247 @@
+use crate::Project;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::{CharIndices, FromStr};
+use thiserror::Error;
+
+/// Boolean expression over project tags, e.g. `work & rust & !archived`.
+///
+/// `&` binds tighter than `|`, `!` negates and parentheses group. A comma is
+/// an alias for `|`, so the historical `-t rust,cli` keeps meaning "either".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagQuery {
+    Tag(String),
+    Not(Box<TagQuery>),
+    And(Vec<TagQuery>),
+    Or(Vec<TagQuery>),
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum TagQueryError {
+    #[error("unexpected '{0}' at position {1} in tag query")]
+    Unexpected(char, usize),
+    #[error("unexpected end of tag query")]
+    UnexpectedEnd,
+}
+
+impl TagQuery {
+    pub fn matches(&self, project: &Project) -> bool {
+        match self {
+            Self::Tag(tag) => project.has_tag(tag),
+            Self::Not(inner) => !inner.matches(project),
+            Self::And(all) => all.iter().all(|q| q.matches(project)),
+            Self::Or(any) => any.iter().any(|q| q.matches(project)),
+        }
+    }
+}
+
+impl fmt::Display for TagQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |f: &mut fmt::Formatter<'_>, terms: &[TagQuery], op: &str| {
+            for (i, term) in terms.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " {op} ")?;
+                }
+                match term {
+                    Self::And(_) | Self::Or(_) => write!(f, "({term})")?,
+                    _ => write!(f, "{term}")?,
+                }
+            }
+            Ok(())
+        };
+
+        match self {
+            Self::Tag(tag) => write!(f, "{tag}"),
+            Self::Not(inner) => match inner.as_ref() {
+                Self::And(_) | Self::Or(_) => write!(f, "!({inner})"),
+                _ => write!(f, "!{inner}"),
+            },
+            Self::And(terms) => join(f, terms, "&"),
+            Self::Or(terms) => join(f, terms, "|"),
+        }
+    }
+}
+
+impl FromStr for TagQuery {
+    type Err = TagQueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            chars: s.char_indices().peekable(),
+        };
+        let query = parser.parse_or()?;
+
+        match parser.next() {
+            None => Ok(query),
+            Some((pos, c)) => Err(TagQueryError::Unexpected(c, pos)),
+        }
+    }
+}
+
+/// Recursive descent parser for:
+///
+/// ```text
+/// or    := and (('|' | ',') and)*
+/// and   := unary ('&' unary)*
+/// unary := '!' unary | '(' or ')' | TAG
+/// ```
+struct Parser<'a> {
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl Parser<'_> {
+    fn peek(&mut self) -> Option<char> {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        self.chars.peek().map(|(_, c)| *c)
+    }
+
+    fn next(&mut self) -> Option<(usize, char)> {
+        self.peek()?;
+        self.chars.next()
+    }
+
+    fn parse_or(&mut self) -> Result<TagQuery, TagQueryError> {
+        let mut terms = vec![self.parse_and()?];
+        while matches!(self.peek(), Some('|' | ',')) {
+            self.next();
+            terms.push(self.parse_and()?);
+        }
+
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            TagQuery::Or(terms)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<TagQuery, TagQueryError> {
+        let mut terms = vec![self.parse_unary()?];
+        while self.peek() == Some('&') {
+            self.next();
+            terms.push(self.parse_unary()?);
+        }
+
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            TagQuery::And(terms)
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<TagQuery, TagQueryError> {
+        match self.next() {
+            None => Err(TagQueryError::UnexpectedEnd),
+            Some((_, '!')) => Ok(TagQuery::Not(Box::new(self.parse_unary()?))),
+            Some((_, '(')) => {
+                let inner = self.parse_or()?;
+                match self.next() {
+                    Some((_, ')')) => Ok(inner),
+                    Some((pos, c)) => Err(TagQueryError::Unexpected(c, pos)),
+                    None => Err(TagQueryError::UnexpectedEnd),
+                }
+            }
+            Some((pos, c)) if is_operator(c) => Err(TagQueryError::Unexpected(c, pos)),
+            Some((_, c)) => {
+                let mut tag = c.to_string();
+                while let Some((_, c)) = self
+                    .chars
+                    .next_if(|(_, c)| !c.is_whitespace() && !is_operator(*c))
+                {
+                    tag.push(c);
+                }
+                Ok(TagQuery::Tag(tag))
+            }
+        }
+    }
+}
+
+fn is_operator(c: char) -> bool {
+    matches!(c, '&' | '|' | ',' | '!' | '(' | ')')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn project(tags: &[&str]) -> Project {
+        Project::new("p".to_string(), PathBuf::from("/tmp/p"), false)
+            .with_tags(tags.iter().map(|t| t.to_string()).collect())
+    }
+
+    fn matches(query: &str, tags: &[&str]) -> bool {
+        query.parse::<TagQuery>().unwrap().matches(&project(tags))
+    }
+
+    #[test]
+    fn test_parse_precedence() {
+        let query: TagQuery = "a | b & !c".parse().unwrap();
+        assert_eq!(
+            query,
+            TagQuery::Or(vec![
+                TagQuery::Tag("a".to_string()),
+                TagQuery::And(vec![
+                    TagQuery::Tag("b".to_string()),
+                    TagQuery::Not(Box::new(TagQuery::Tag("c".to_string()))),
+                ]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_and_not() {
+        assert!(matches("work & rust & !archived", &["work", "rust"]));
+        assert!(!matches(
+            "work & rust & !archived",
+            &["work", "rust", "archived"]
+        ));
+        assert!(!matches("work & rust", &["work"]));
+    }
+
+    #[test]
+    fn test_or_and_comma() {
+        assert!(matches("client-a | client-b", &["client-b"]));
+        assert!(matches("rust,cli", &["cli"]));
+        assert!(!matches("rust,cli", &["go"]));
+    }
+
+    #[test]
+    fn test_grouping() {
+        assert!(matches("!(a | b)", &["c"]));
+        assert!(!matches("!(a | b)", &["b"]));
+        assert!(matches("(a | b) & c", &["b", "c"]));
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        for query in ["a | b & !c", "!(a | b)", "(a | b) & c"] {
+            let parsed: TagQuery = query.parse().unwrap();
+            assert_eq!(parsed.to_string().parse::<TagQuery>().unwrap(), parsed);
+        }
+        assert_eq!("a,b".parse::<TagQuery>().unwrap().to_string(), "a | b");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "".parse::<TagQuery>().unwrap_err(),
+            TagQueryError::UnexpectedEnd
+        );
+        assert_eq!(
+            "a &".parse::<TagQuery>().unwrap_err(),
+            TagQueryError::UnexpectedEnd
+        );
+        assert_eq!(
+            "a b".parse::<TagQuery>().unwrap_err(),
+            TagQueryError::Unexpected('b', 2)
+        );
+        assert_eq!(
+            "(a".parse::<TagQuery>().unwrap_err(),
+            TagQueryError::UnexpectedEnd
+        );
+        assert_eq!(
+            "a)".parse::<TagQuery>().unwrap_err(),
+            TagQueryError::Unexpected(')', 1)
+        );
+    }
+}
@@ -0,0 +1,40 @@
+use super::picker::{shell_command, shell_quote};
+use anyhow::{bail, Result};
+use std::fs;
+use std::io::Write;
+
+#[cfg(not(windows))]
+const FALLBACK_EDITOR: &str = "vi";
+#[cfg(windows)]
+const FALLBACK_EDITOR: &str = "notepad";
+
+/// Opens `$VISUAL` or `$EDITOR` on a temp file holding `initial` and returns
+/// what was saved. The editor runs through the shell, so values such as
+/// `code --wait` work.
+pub fn edit(initial: &str, file_name: &str) -> Result<String> {
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|e| !e.is_empty()))
+        .unwrap_or_else(|| FALLBACK_EDITOR.to_string());
+
+    // Created exclusively under a random name and removed when dropped, on
+    // every path out of here.
+    let mut file = tempfile::Builder::new()
+        .prefix("bvo-")
+        .suffix(&format!("-{file_name}"))
+        .tempfile()?;
+    file.write_all(initial.as_bytes())?;
+    file.flush()?;
+
+    let command_line = format!("{editor} {}", shell_quote(&file.path().to_string_lossy()));
+    let status = shell_command(&command_line).status();
+    // Read back by path: editors may replace the file rather than write to it.
+    let content = fs::read_to_string(file.path());
+
+    let status = status?;
+    if !status.success() {
+        bail!("editor exited with {status}, nothing saved");
+    }
+
+    Ok(content?)
+}
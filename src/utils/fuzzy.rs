@@ -18,4 +18,22 @@ macro_rules! fuzzy_scorer {
                 .map(|r| r.score as i64)
         };
     };
+    // `fuzzy_scorer!(scorer_name, Type, |item, label| text)` matches against
+    // the text returned by the closure instead of the label.
+    ($name:ident, $T:ty, $text:expr) => {
+        let matcher =
+            ::std::cell::RefCell::new(::frizbee::Matcher::new("", &::frizbee::Config::default()));
+        let text = $text;
+        let $name: ::inquire::type_aliases::Scorer<$T> = &|input, item, str_val, _| {
+            if input.is_empty() {
+                return Some(0);
+            }
+            let needle = input.replace(' ', "");
+            let haystack: String = text(item, str_val);
+            let mut m = matcher.borrow_mut();
+            m.set_needle(&needle);
+            m.smith_waterman_one(haystack.as_bytes(), 0, true)
+                .map(|r| r.score as i64)
+        };
+    };
 }
@@ -1,2 +1,5 @@
+pub mod editor;
+pub mod fs;
 pub mod fuzzy;
+pub mod picker;
 pub mod terminal;
@@ -0,0 +1,352 @@
+use anyhow::Result;
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{self, ClearType},
+};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::{stderr, Write};
+use std::process::{Command, Stdio};
+
+/// Filter and cursor state of [`pick_with_preview`], kept apart from the
+/// terminal so it can be tested.
+struct PickerState {
+    labels: Vec<String>,
+    /// Text matched against the query, `labels` when `None`.
+    search: Option<Vec<String>>,
+    query: String,
+    /// Indices into `labels`, best match first.
+    matches: Vec<usize>,
+    cursor: usize,
+}
+
+impl PickerState {
+    fn new(labels: Vec<String>, query: &str) -> Self {
+        let mut state = Self {
+            labels,
+            search: None,
+            query: query.to_string(),
+            matches: Vec::new(),
+            cursor: 0,
+        };
+        state.refilter();
+        state
+    }
+
+    /// Recomputes matches for the current query. Like `fuzzy_scorer!`, spaces
+    /// are stripped so "my proj" matches "my-project".
+    fn refilter(&mut self) {
+        let needle = self.query.replace(' ', "");
+        self.matches = if needle.is_empty() {
+            (0..self.labels.len()).collect()
+        } else {
+            let haystacks = self.search.as_ref().unwrap_or(&self.labels);
+            frizbee::match_list(&needle, haystacks, &frizbee::Config::default())
+                .into_iter()
+                .map(|m| m.index as usize)
+                .collect()
+        };
+        self.cursor = 0;
+    }
+
+    fn with_search(mut self, search: Vec<String>) -> Self {
+        self.search = Some(search);
+        self.refilter();
+        self
+    }
+
+    fn selected(&self) -> Option<usize> {
+        self.matches.get(self.cursor).copied()
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let len = self.matches.len() as isize;
+        self.cursor = (self.cursor as isize + delta).rem_euclid(len) as usize;
+    }
+}
+
+enum Outcome {
+    Selected(usize),
+    Cancelled,
+}
+
+/// Fuzzy picker that shows `preview` of the highlighted item below the list.
+/// The query is matched against `search` of each item when given, otherwise
+/// against its label.
+///
+/// Draws on stderr so stdout stays free for the selected path. Returns `None`
+/// when the user cancels with `Esc` or `Ctrl-C`.
+pub fn pick_with_preview<T, F>(
+    message: &str,
+    items: Vec<T>,
+    query: &str,
+    search: Option<fn(&T) -> String>,
+    preview: F,
+) -> Result<Option<T>>
+where
+    T: Display,
+    F: Fn(&T) -> Vec<String>,
+{
+    let labels = items.iter().map(|i| i.to_string()).collect();
+    let mut state = PickerState::new(labels, query);
+    if let Some(search) = search {
+        state = state.with_search(items.iter().map(search).collect());
+    }
+
+    terminal::enable_raw_mode()?;
+    execute!(stderr(), terminal::EnterAlternateScreen, cursor::Hide)?;
+
+    let outcome = run(message, &mut state, &items, &preview);
+
+    execute!(stderr(), cursor::Show, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+
+    Ok(match outcome? {
+        Outcome::Selected(index) => items.into_iter().nth(index),
+        Outcome::Cancelled => None,
+    })
+}
+
+fn run<T, F>(message: &str, state: &mut PickerState, items: &[T], preview: &F) -> Result<Outcome>
+where
+    F: Fn(&T) -> Vec<String>,
+{
+    // Previews may hit the disk (git status, README), so each is built once.
+    let mut cache: HashMap<usize, Vec<String>> = HashMap::new();
+
+    loop {
+        let preview_lines = match state.selected() {
+            Some(index) => cache
+                .entry(index)
+                .or_insert_with(|| preview(&items[index]))
+                .as_slice(),
+            None => &[],
+        };
+        draw(message, state, preview_lines)?;
+
+        let Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event::read()?
+        else {
+            continue;
+        };
+
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        match code {
+            KeyCode::Enter => {
+                if let Some(index) = state.selected() {
+                    return Ok(Outcome::Selected(index));
+                }
+            }
+            KeyCode::Esc => return Ok(Outcome::Cancelled),
+            KeyCode::Char('c') if ctrl => return Ok(Outcome::Cancelled),
+            KeyCode::Up => state.move_by(-1),
+            KeyCode::Char('p') if ctrl => state.move_by(-1),
+            KeyCode::Down | KeyCode::Tab => state.move_by(1),
+            KeyCode::Char('n') if ctrl => state.move_by(1),
+            KeyCode::Backspace => {
+                state.query.pop();
+                state.refilter();
+            }
+            KeyCode::Char(c) if !ctrl => {
+                state.query.push(c);
+                state.refilter();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Renders the prompt, up to half the screen of matches, and the preview.
+fn draw(message: &str, state: &PickerState, preview: &[String]) -> Result<()> {
+    // Some pseudo-terminals report a zero size; fall back to 80x24.
+    let (width, height) = match terminal::size()? {
+        (0, _) | (_, 0) => (80, 24),
+        size => size,
+    };
+    let width = width as usize;
+    let list_height = (height as usize / 2).saturating_sub(2).max(1);
+    let preview_height = (height as usize).saturating_sub(list_height + 3);
+
+    let mut out = stderr();
+    queue!(out, cursor::MoveTo(0, 0), terminal::Clear(ClearType::All))?;
+    queue!(out, Print(format!("? {message} {}", state.query)))?;
+
+    let offset = state.cursor.saturating_sub(list_height - 1);
+    let visible = state.matches.iter().enumerate().skip(offset);
+    for (row, (pos, &index)) in visible.take(list_height).enumerate() {
+        queue!(out, cursor::MoveTo(0, row as u16 + 1))?;
+        let label = truncate(&state.labels[index], width.saturating_sub(2));
+        if pos == state.cursor {
+            queue!(
+                out,
+                SetAttribute(Attribute::Reverse),
+                Print(format!("> {label}")),
+                SetAttribute(Attribute::Reset)
+            )?;
+        } else {
+            queue!(out, Print(format!("  {label}")))?;
+        }
+    }
+
+    queue!(
+        out,
+        cursor::MoveTo(0, list_height as u16 + 1),
+        Print("─".repeat(width))
+    )?;
+    for (row, line) in preview.iter().take(preview_height).enumerate() {
+        queue!(
+            out,
+            cursor::MoveTo(0, (list_height + 2 + row) as u16),
+            Print(truncate(line, width))
+        )?;
+    }
+
+    out.flush()?;
+    Ok(())
+}
+
+/// Runs an external picker such as `fzf` or `sk`, feeding `lines` on stdin,
+/// and returns the index of the line it prints back.
+///
+/// `query` and `preview` are passed as `--query` and `--preview`, which both
+/// fzf and skim understand. A non-zero exit (cancel, no match) yields `None`.
+pub fn pick_external(
+    command: &str,
+    lines: &[String],
+    query: &str,
+    preview: Option<&str>,
+) -> Result<Option<usize>> {
+    let mut command_line = command.to_string();
+    if !query.is_empty() {
+        command_line.push_str(&format!(" --query {}", shell_quote(query)));
+    }
+    if let Some(preview) = preview {
+        command_line.push_str(&format!(" --preview {}", shell_quote(preview)));
+    }
+
+    let mut child = shell_command(&command_line)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // The picker may exit before reading everything; that is not an error.
+        let _ = stdin.write_all(lines.join("\n").as_bytes());
+    }
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let selected = stdout.trim_end_matches(['\r', '\n']);
+
+    Ok(lines.iter().position(|line| line == selected))
+}
+
+/// Runs `command_line` through the platform shell.
+#[cfg(not(windows))]
+pub fn shell_command(command_line: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(command_line);
+    command
+}
+
+#[cfg(windows)]
+pub fn shell_command(command_line: &str) -> Command {
+    let mut command = Command::new("cmd");
+    command.arg("/C").arg(command_line);
+    command
+}
+
+/// Quotes `s` as a single POSIX shell word.
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+fn truncate(s: &str, width: usize) -> String {
+    s.chars().take(width).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(query: &str) -> PickerState {
+        let labels = ["bivio", "my-project", "website"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        PickerState::new(labels, query)
+    }
+
+    #[test]
+    fn test_empty_query_keeps_order() {
+        let state = state("");
+        assert_eq!(state.matches, vec![0, 1, 2]);
+        assert_eq!(state.selected(), Some(0));
+    }
+
+    #[test]
+    fn test_query_filters_and_ignores_spaces() {
+        let state = state("my proj");
+        assert_eq!(state.selected(), Some(1));
+        assert_eq!(state.matches.len(), 1);
+    }
+
+    #[test]
+    fn test_query_matches_search_text() {
+        let state = state("invoices").with_search(vec![
+            "bivio".to_string(),
+            "my-project billing and invoices".to_string(),
+            "website".to_string(),
+        ]);
+        assert_eq!(state.matches, vec![1]);
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("plain"), "'plain'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_pick_external() {
+        let lines: Vec<String> = ["alpha", "beta", "gamma"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        assert_eq!(
+            pick_external("grep beta", &lines, "", None).unwrap(),
+            Some(1)
+        );
+        assert_eq!(pick_external("grep nope", &lines, "", None).unwrap(), None);
+        assert_eq!(
+            pick_external("head -n1", &lines, "", None).unwrap(),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_cursor_wraps() {
+        let mut state = state("");
+        state.move_by(-1);
+        assert_eq!(state.selected(), Some(2));
+        state.move_by(1);
+        assert_eq!(state.selected(), Some(0));
+    }
+}
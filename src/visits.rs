@@ -0,0 +1,210 @@
+use chrono::{DateTime, NaiveDate, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Visits kept with their exact time.
+pub const RECENT_LIMIT: usize = 100;
+
+/// Days of older visits kept as counts. Past this the oldest day absorbs the
+/// next one, so no visit is lost but ancient ones only ever get older.
+pub const DAY_LIMIT: usize = 90;
+
+/// Bounded log of when a project or worktree was opened.
+///
+/// The latest [`RECENT_LIMIT`] visits are kept as Unix timestamps; older ones
+/// are compacted into per-day counts (UTC), at most [`DAY_LIMIT`] of them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VisitLog {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    recent: Vec<i64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    days: BTreeMap<NaiveDate, u32>,
+}
+
+impl VisitLog {
+    pub fn is_empty(&self) -> bool {
+        self.recent.is_empty() && self.days.is_empty()
+    }
+
+    pub fn record(&mut self, at: DateTime<Utc>) {
+        let at = at.timestamp();
+        let index = self.recent.partition_point(|t| *t <= at);
+        self.recent.insert(index, at);
+        self.compact();
+    }
+
+    fn compact(&mut self) {
+        self.fold(self.recent.len().saturating_sub(RECENT_LIMIT));
+    }
+
+    /// Moves the `count` oldest exact visits into their days, then keeps the
+    /// days within [`DAY_LIMIT`].
+    fn fold(&mut self, count: usize) {
+        for timestamp in self.recent.drain(..count) {
+            if let Some(at) = DateTime::from_timestamp(timestamp, 0) {
+                *self.days.entry(at.date_naive()).or_default() += 1;
+            }
+        }
+
+        while self.days.len() > DAY_LIMIT {
+            let mut oldest = self.days.keys().copied();
+            let (Some(first), Some(second)) = (oldest.next(), oldest.next()) else {
+                break;
+            };
+            let count = self.days.remove(&second).unwrap_or_default();
+            *self.days.entry(first).or_default() += count;
+        }
+    }
+
+    /// Number of visits in the log.
+    pub fn total(&self) -> u64 {
+        self.recent.len() as u64 + self.days.values().map(|c| u64::from(*c)).sum::<u64>()
+    }
+
+    /// Exact times of the latest visits, most recent first.
+    pub fn recent(&self) -> impl Iterator<Item = DateTime<Utc>> + '_ {
+        self.recent
+            .iter()
+            .rev()
+            .filter_map(|t| DateTime::from_timestamp(*t, 0))
+    }
+
+    pub fn last(&self) -> Option<DateTime<Utc>> {
+        self.recent().next().or_else(|| {
+            self.days
+                .keys()
+                .next_back()
+                .map(|day| Utc.from_utc_datetime(&day.and_time(NaiveTime::MIN)))
+        })
+    }
+
+    /// Sum of `weight` over every visit. Compacted visits are taken at noon
+    /// of their day.
+    pub fn weighted(&self, weight: impl Fn(DateTime<Utc>) -> f64) -> f64 {
+        // Folded from 0.0: an empty float `sum` is -0.0.
+        let exact = self.recent().map(&weight).fold(0.0, |sum, w| sum + w);
+        self.days.iter().fold(exact, |sum, (day, count)| {
+            let noon = day.and_hms_opt(12, 0, 0).unwrap_or_default();
+            sum + f64::from(*count) * weight(Utc.from_utc_datetime(&noon))
+        })
+    }
+
+    /// Scales the log by `factor`, dropping days that reach zero. Exact visits
+    /// are folded into their days first so the whole log shrinks alike.
+    pub fn age(&mut self, factor: f64) {
+        self.fold(self.recent.len());
+        let target = (self.total() as f64 * factor).round() as u64;
+        for count in self.days.values_mut() {
+            *count = (f64::from(*count) * factor).floor() as u32;
+        }
+        // Rounding each day down loses less than one visit per day; the
+        // latest days get them back so the total is rounded only once.
+        let missing = target.saturating_sub(self.total());
+        for count in self.days.values_mut().rev().take(missing as usize) {
+            *count += 1;
+        }
+        self.days.retain(|_, count| *count > 0);
+    }
+
+    /// Union of two logs of the same project, as recorded on two machines.
+    pub fn merge(&self, other: &Self) -> Self {
+        // A visit present on both sides is the same visit, so each timestamp
+        // is kept as often as the side that has it most.
+        let mut counts: BTreeMap<i64, usize> = BTreeMap::new();
+        for log in [self, other] {
+            let mut own: BTreeMap<i64, usize> = BTreeMap::new();
+            for timestamp in &log.recent {
+                *own.entry(*timestamp).or_default() += 1;
+            }
+            for (timestamp, count) in own {
+                let entry = counts.entry(timestamp).or_default();
+                *entry = (*entry).max(count);
+            }
+        }
+        let recent = counts
+            .into_iter()
+            .flat_map(|(timestamp, count)| std::iter::repeat_n(timestamp, count))
+            .collect();
+
+        let mut days = self.days.clone();
+        for (day, count) in &other.days {
+            let entry = days.entry(*day).or_default();
+            *entry = (*entry).max(*count);
+        }
+
+        let mut merged = Self { recent, days };
+        merged.compact();
+        merged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeDelta;
+
+    #[test]
+    fn test_log_is_bounded() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 8, 0, 0).unwrap();
+        let mut log = VisitLog::default();
+        for i in 0..1_000 {
+            log.record(start + TimeDelta::hours(i * 9));
+        }
+
+        assert_eq!(log.total(), 1_000);
+        assert_eq!(log.recent().count(), RECENT_LIMIT);
+        assert_eq!(log.days.len(), DAY_LIMIT);
+        assert_eq!(log.last(), Some(start + TimeDelta::hours(999 * 9)));
+
+        // Oldest day holds everything squeezed out of the window.
+        assert_eq!(*log.days.keys().next().unwrap(), start.date_naive());
+    }
+
+    #[test]
+    fn test_recent_is_newest_first() {
+        let now = Utc::now();
+        let mut log = VisitLog::default();
+        log.record(now - TimeDelta::hours(1));
+        log.record(now);
+        log.record(now - TimeDelta::hours(2));
+
+        let hours: Vec<i64> = log.recent().map(|t| (now - t).num_hours()).collect();
+        assert_eq!(hours, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_merge_unites_visits() {
+        let now = Utc::now();
+        let mut base = VisitLog::default();
+        base.record(now - TimeDelta::days(1));
+
+        let mut ours = base.clone();
+        ours.record(now - TimeDelta::hours(2));
+        let mut theirs = base.clone();
+        theirs.record(now - TimeDelta::hours(1));
+
+        let merged = ours.merge(&theirs);
+        assert_eq!(merged.total(), 3);
+        assert_eq!(merged, theirs.merge(&ours));
+
+        // Visits within the same second are kept apart.
+        let mut twice = VisitLog::default();
+        twice.record(now);
+        twice.record(now);
+        assert_eq!(twice.merge(&twice).total(), 2);
+    }
+
+    #[test]
+    fn test_age_scales_every_visit() {
+        let start = Utc.with_ymd_and_hms(2026, 1, 1, 8, 0, 0).unwrap();
+        let mut log = VisitLog::default();
+        for i in 0..(RECENT_LIMIT as i64 + 10) {
+            log.record(start + TimeDelta::minutes(i));
+        }
+        assert_eq!(log.total(), RECENT_LIMIT as u64 + 10);
+
+        log.age(0.5);
+        assert_eq!(log.total(), (RECENT_LIMIT as u64 + 10) / 2);
+        assert_eq!(log.recent().count(), 0);
+    }
+}